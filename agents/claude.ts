@@ -2,9 +2,11 @@ import { type Options, query, type SDKMessage } from "@anthropic-ai/claude-agent
 import type { Effort } from "../external.ts";
 import { ghPullfrogMcpName } from "../external.ts";
 import packageJson from "../package.json" with { type: "json" };
+import type { RunBudgetTracker } from "../utils/budget.ts";
 import { log } from "../utils/cli.ts";
 import { installFromNpmTarball } from "../utils/install.ts";
-import { type AgentRunContext, agent } from "./shared.ts";
+import type { AgentEventStream } from "./events.ts";
+import { type AgentOutputContext, type AgentRunContext, agent } from "./shared.ts";
 
 // Model selection based on effort level
 // Note: mini uses Haiku for speed, auto uses opusplan for balance, max uses Opus for capability
@@ -61,8 +63,15 @@ export const claude = agent({
       log.info(`» disallowed tools: ${disallowedTools.join(", ")}`);
     }
 
+    // the SDK takes a controller rather than a signal
+    const abortController = new AbortController();
+    ctx.budget.signal.addEventListener("abort", () => abortController.abort(), { once: true });
+
+    const maxUsd = ctx.budget.budget.maxUsd;
     const queryOptions: Options = {
       permissionMode: "bypassPermissions" as const,
+      abortController,
+      ...(maxUsd !== undefined && { maxBudgetUsd: maxUsd }),
       disallowedTools,
       mcpServers: {
         [ghPullfrogMcpName]: { type: "http", url: ctx.mcpServerUrl },
@@ -78,10 +87,16 @@ export const claude = agent({
     });
 
     // Stream the results
-    for await (const message of queryInstance) {
-      log.debug(JSON.stringify(message, null, 2));
-      const handler = messageHandlers[message.type];
-      await handler(message as never);
+    const handleMessage = createOutputHandler(ctx);
+    try {
+      for await (const message of queryInstance) {
+        log.debug(JSON.stringify(message, null, 2));
+        await handleMessage(message);
+      }
+    } catch (error) {
+      // aborting for the budget ends the stream with an error; the cutoff is reported by the caller
+      if (!ctx.budget.signal.aborted) throw error;
+      return { success: false, output: "" };
     }
 
     return {
@@ -89,12 +104,54 @@ export const claude = agent({
       output: "",
     };
   },
+  createOutputHandler,
 });
 
+function createOutputHandler(ctx: AgentOutputContext) {
+  const countedMessageIds = new Set<string>();
+  return async (message: SDKMessage) => {
+    ctx.events.recordRaw(message);
+    trackUsage(ctx.budget, message, countedMessageIds);
+    const handler = messageHandlers[message.type];
+    await handler(message as never, ctx.events);
+  };
+}
+
+/**
+ * Report usage to the run budget: per API call while streaming, then the run totals and cost.
+ * An assistant message is emitted once per content block, so calls are counted by message id.
+ */
+function trackUsage(budget: RunBudgetTracker, message: SDKMessage, countedMessageIds: Set<string>) {
+  if (message.type === "assistant") {
+    const { id, usage } = message.message;
+    if (!usage || countedMessageIds.has(id)) return;
+    countedMessageIds.add(id);
+    const cacheRead = usage.cache_read_input_tokens ?? 0;
+    const cacheWrite = usage.cache_creation_input_tokens ?? 0;
+    budget.add({
+      inputTokens: usage.input_tokens + cacheRead + cacheWrite,
+      outputTokens: usage.output_tokens,
+      cacheReadTokens: cacheRead,
+      cacheWriteTokens: cacheWrite,
+    });
+  } else if (message.type === "result") {
+    const cacheRead = message.usage.cache_read_input_tokens ?? 0;
+    const cacheWrite = message.usage.cache_creation_input_tokens ?? 0;
+    budget.set({
+      inputTokens: message.usage.input_tokens + cacheRead + cacheWrite,
+      outputTokens: message.usage.output_tokens,
+      cacheReadTokens: cacheRead,
+      cacheWriteTokens: cacheWrite,
+      costUsd: message.total_cost_usd,
+    });
+  }
+}
+
 type SDKMessageType = SDKMessage["type"];
 
 type SDKMessageHandler<type extends SDKMessageType = SDKMessageType> = (
-  data: Extract<SDKMessage, { type: type }>
+  data: Extract<SDKMessage, { type: type }>,
+  events: AgentEventStream
 ) => void | Promise<void>;
 
 type SDKMessageHandlers = {
@@ -105,43 +162,51 @@ type SDKMessageHandlers = {
 const bashToolIds = new Set<string>();
 
 const messageHandlers: SDKMessageHandlers = {
-  assistant: (data) => {
+  assistant: (data, events) => {
     if (data.message?.content) {
       for (const content of data.message.content) {
         if (content.type === "text" && content.text?.trim()) {
-          log.box(content.text.trim(), { title: "Claude" });
+          events.emit({ type: "message", text: content.text.trim() });
         } else if (content.type === "tool_use") {
           // Track bash tool IDs
           if (content.name === "bash" && content.id) {
             bashToolIds.add(content.id);
           }
 
-          log.toolCall({
-            toolName: content.name,
+          events.emit({
+            type: "tool_call",
+            id: content.id,
+            tool: content.name,
             input: content.input,
           });
         }
       }
     }
   },
-  user: (data) => {
+  user: (data, events) => {
     if (data.message?.content) {
       for (const content of data.message.content) {
         if (content.type === "tool_result") {
           const toolUseId = (content as any).tool_use_id;
           const isBashTool = toolUseId && bashToolIds.has(toolUseId);
+          const outputContent =
+            typeof content.content === "string"
+              ? content.content
+              : Array.isArray(content.content)
+                ? content.content
+                    .map((c: any) => (typeof c === "string" ? c : c.text || JSON.stringify(c)))
+                    .join("\n")
+                : String(content.content);
+
+          events.emit({
+            type: "tool_result",
+            id: toolUseId,
+            output: outputContent,
+            isError: content.is_error === true,
+          });
 
           if (isBashTool) {
             // Log bash output in a collapsed group
-            const outputContent =
-              typeof content.content === "string"
-                ? content.content
-                : Array.isArray(content.content)
-                  ? content.content
-                      .map((c: any) => (typeof c === "string" ? c : c.text || JSON.stringify(c)))
-                      .join("\n")
-                  : String(content.content);
-
             log.startGroup(`bash output`);
             if (content.is_error) {
               log.warning(outputContent);
@@ -152,9 +217,7 @@ const messageHandlers: SDKMessageHandlers = {
             // Clean up the tracked ID
             bashToolIds.delete(toolUseId);
           } else if (content.is_error) {
-            const errorContent =
-              typeof content.content === "string" ? content.content : String(content.content);
-            log.warning(`Tool error: ${errorContent}`);
+            log.warning(`Tool error: ${outputContent}`);
           }
         }
       }
@@ -185,6 +248,8 @@ const messageHandlers: SDKMessageHandlers = {
           String(outputTokens),
         ],
       ]);
+    } else if (data.subtype === "error_max_budget_usd") {
+      log.error(`Cost budget reached: $${data.total_cost_usd.toFixed(4)}`);
     } else if (data.subtype === "error_max_turns") {
       log.error(`Max turns reached: ${JSON.stringify(data)}`);
     } else if (data.subtype === "error_during_execution") {
@@ -11,6 +11,7 @@ import type { Effort } from "../external.ts";
 import { ghPullfrogMcpName } from "../external.ts";
 import { log } from "../utils/cli.ts";
 import { installFromNpmTarball } from "../utils/install.ts";
+import type { AgentEventStream } from "./events.ts";
 import { type AgentRunContext, agent } from "./shared.ts";
 
 // model configuration based on effort level
@@ -129,14 +130,26 @@ export const codex = agent({
     const thread = codex.startThread(threadOptions);
 
     try {
-      const streamedTurn = await thread.runStreamed(ctx.instructions.full);
+      const streamedTurn = await thread.runStreamed(ctx.instructions.full, {
+        signal: ctx.budget.signal,
+      });
 
       let finalOutput = "";
       for await (const event of streamedTurn.events) {
+        ctx.events.recordRaw(event);
         const handler = messageHandlers[event.type];
         log.debug(JSON.stringify(event, null, 2));
         if (handler) {
-          handler(event as never);
+          handler(event as never, ctx.events);
+        }
+
+        if (event.type === "turn.completed") {
+          // input_tokens already includes cached_input_tokens
+          ctx.budget.set({
+            inputTokens: event.usage.input_tokens,
+            outputTokens: event.usage.output_tokens,
+            cacheReadTokens: event.usage.cached_input_tokens,
+          });
         }
 
         if (event.type === "item.completed" && event.item.type === "agent_message") {
@@ -164,7 +177,8 @@ export const codex = agent({
 const commandExecutionIds = new Set<string>();
 
 type ThreadEventHandler<type extends ThreadEvent["type"]> = (
-  event: Extract<ThreadEvent, { type: type }>
+  event: Extract<ThreadEvent, { type: type }>,
+  events: AgentEventStream
 ) => void;
 
 const messageHandlers: {
@@ -193,19 +207,23 @@ const messageHandlers: {
   "turn.failed": (event) => {
     log.error(`Turn failed: ${event.error.message}`);
   },
-  "item.started": (event) => {
+  "item.started": (event, events) => {
     const item = event.item;
     if (item.type === "command_execution") {
       commandExecutionIds.add(item.id);
-      log.toolCall({
-        toolName: item.command,
+      events.emit({
+        type: "tool_call",
+        id: item.id,
+        tool: item.command,
         input: (item as any).args || {},
       });
     } else if (item.type === "agent_message") {
       // Will be handled on completion
     } else if (item.type === "mcp_tool_call") {
-      log.toolCall({
-        toolName: item.tool,
+      events.emit({
+        type: "tool_call",
+        id: item.id,
+        tool: item.tool,
         input: {
           server: item.server,
           ...((item as any).arguments || {}),
@@ -222,11 +240,17 @@ const messageHandlers: {
       }
     }
   },
-  "item.completed": (event) => {
+  "item.completed": (event, events) => {
     const item = event.item;
     if (item.type === "agent_message") {
-      log.box(item.text.trim(), { title: "Codex" });
+      events.emit({ type: "message", text: item.text.trim() });
     } else if (item.type === "command_execution") {
+      events.emit({
+        type: "tool_result",
+        id: item.id,
+        output: item.aggregated_output || "",
+        isError: item.status === "failed" || (item.exit_code !== undefined && item.exit_code !== 0),
+      });
       const isTracked = commandExecutionIds.has(item.id);
       if (isTracked) {
         log.startGroup(`bash output`);
@@ -239,6 +263,12 @@ const messageHandlers: {
         commandExecutionIds.delete(item.id);
       }
     } else if (item.type === "mcp_tool_call") {
+      events.emit({
+        type: "tool_result",
+        id: item.id,
+        output: item.error?.message ?? JSON.stringify((item as any).result?.content ?? ""),
+        isError: item.status === "failed",
+      });
       if (item.status === "failed" && item.error) {
         log.warning(`MCP tool call failed: ${item.error.message}`);
       }
@@ -155,7 +155,7 @@ export const cursor = agent({
           // we deduplicate by model_call_id to avoid logging the same message twice
           if (!loggedModelCallIds.has(event.model_call_id)) {
             loggedModelCallIds.add(event.model_call_id);
-            log.box(text, { title: "Cursor" });
+            ctx.events.emit({ type: "message", text });
           }
         } else {
           // message without model_call_id - log it immediately
@@ -163,7 +163,7 @@ export const cursor = agent({
           // 1. the final summary message might only be emitted without model_call_id
           // 2. messages that don't get re-emitted with model_call_id
           // without this, the final comprehensive summary wouldn't print (as we discovered)
-          log.box(text, { title: "Cursor" });
+          ctx.events.emit({ type: "message", text });
         }
       },
       tool_call: (event: CursorToolCallEvent) => {
@@ -173,18 +173,29 @@ export const cursor = agent({
           const builtinToolCall = (event.tool_call as any)?.builtinToolCall;
 
           if (mcpToolCall?.args?.toolName && mcpToolCall?.args?.args) {
-            log.toolCall({
-              toolName: mcpToolCall.args.toolName,
+            ctx.events.emit({
+              type: "tool_call",
+              id: event.call_id,
+              tool: mcpToolCall.args.toolName,
               input: mcpToolCall.args.args,
             });
           } else if (builtinToolCall?.args?.name && builtinToolCall?.args?.args) {
-            log.toolCall({
-              toolName: builtinToolCall.args.name,
+            ctx.events.emit({
+              type: "tool_call",
+              id: event.call_id,
+              tool: builtinToolCall.args.name,
               input: builtinToolCall.args.args,
             });
           }
         } else if (event.subtype === "completed") {
-          const isError = event.tool_call?.mcpToolCall?.result?.success?.isError;
+          const result = event.tool_call?.mcpToolCall?.result?.success;
+          const isError = result?.isError;
+          ctx.events.emit({
+            type: "tool_result",
+            id: event.call_id,
+            output: (result?.content ?? []).map((part) => part.text?.text ?? "").join("\n"),
+            isError: isError === true,
+          });
           if (isError) {
             log.warning("Tool call failed");
           }
@@ -228,6 +239,8 @@ export const cursor = agent({
           cwd: process.cwd(),
           env: process.env,
           stdio: ["ignore", "pipe", "pipe"], // Ignore stdin, pipe stdout/stderr
+          // cursor reports no usage, so only the time limit applies
+          signal: ctx.budget.signal,
         });
 
         let stdout = "";
@@ -243,6 +256,7 @@ export const cursor = agent({
 
           try {
             const event = JSON.parse(text) as CursorEvent;
+            ctx.events.recordRaw(event);
             log.debug(JSON.stringify(event, null, 2));
 
             // skip empty thinking deltas
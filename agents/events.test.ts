@@ -0,0 +1,60 @@
+import { mkdtempSync, readFileSync, rmSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import * as cli from '../utils/cli.ts';
+import { AgentEventStream, type RecordedAgentEvent } from './events.ts';
+
+describe('AgentEventStream', () => {
+  let dir: string;
+
+  beforeEach(() => {
+    dir = mkdtempSync(join(tmpdir(), 'pullfrog-events-test-'));
+    vi.spyOn(cli.log, 'box').mockImplementation(() => {});
+    vi.spyOn(cli.log, 'toolCall').mockImplementation(() => {});
+  });
+
+  afterEach(() => {
+    vi.unstubAllEnvs();
+    vi.restoreAllMocks();
+    rmSync(dir, { recursive: true, force: true });
+  });
+
+  it('logs messages and tool calls', () => {
+    const events = new AgentEventStream({ title: 'Claude Code' });
+    events.emit({ type: 'message', text: 'Looking at the diff.' });
+    events.emit({ type: 'tool_call', id: 't1', tool: 'checkout_pr', input: { pull_number: 3 } });
+    events.emit({ type: 'tool_result', id: 't1', output: 'ok', isError: false });
+    expect(cli.log.box).toHaveBeenCalledWith('Looking at the diff.', { title: 'Claude Code' });
+    expect(cli.log.toolCall).toHaveBeenCalledWith({ toolName: 'checkout_pr', input: { pull_number: 3 } });
+    expect(cli.log.toolCall).toHaveBeenCalledTimes(1);
+  });
+
+  it('records every event as a JSONL line', () => {
+    const transcriptPath = join(dir, 'transcript.jsonl');
+    const events = new AgentEventStream({ title: 'Codex CLI', transcriptPath });
+    events.emit({ type: 'tool_call', tool: 'bash', input: { command: 'ls' } });
+    events.emit({ type: 'error', message: 'boom' });
+
+    const lines = readFileSync(transcriptPath, 'utf-8').trimEnd().split('\n');
+    const recorded = lines.map((line) => JSON.parse(line) as RecordedAgentEvent);
+    expect(recorded).toEqual([
+      { time: expect.any(String), type: 'tool_call', tool: 'bash', input: { command: 'ls' } },
+      { time: expect.any(String), type: 'error', message: 'boom' },
+    ]);
+  });
+
+  it('redacts the runner secrets from both transcripts', () => {
+    vi.stubEnv('ANTHROPIC_API_KEY', 'runner-held-key-value');
+    const transcriptPath = join(dir, 'transcript.jsonl');
+    const rawTranscriptPath = join(dir, 'raw.jsonl');
+    const events = new AgentEventStream({ title: 'Claude Code', transcriptPath, rawTranscriptPath });
+    events.recordRaw({ type: 'assistant', text: 'echo runner-held-key-value' });
+    events.emit({ type: 'tool_result', id: 't1', output: 'runner-held-key-value', isError: false });
+
+    for (const path of [transcriptPath, rawTranscriptPath]) {
+      const content = readFileSync(path, 'utf-8');
+      expect(content).not.toContain('runner-held-key-value');
+      expect(content).toContain('[REDACTED_SECRET]');
+    }
+  });
+});
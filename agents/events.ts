@@ -0,0 +1,72 @@
+import { appendFileSync, writeFileSync } from "node:fs";
+import type { AgentUsage } from "../utils/budget.ts";
+import { log } from "../utils/cli.ts";
+import { redactSecrets } from "../utils/secrets.ts";
+
+/**
+ * Agent activity in a common shape, emitted by every adapter whatever its CLI's output format.
+ * Usage and errors are emitted once per run by the shared agent wrapper.
+ */
+export type AgentEvent =
+  | { type: "message"; text: string }
+  | { type: "tool_call"; id?: string | undefined; tool: string; input: unknown }
+  | { type: "tool_result"; id?: string | undefined; output: string; isError: boolean }
+  | { type: "usage"; usage: AgentUsage }
+  | { type: "error"; message: string };
+
+/** a line of the JSONL transcript */
+export type RecordedAgentEvent = AgentEvent & { time: string };
+
+interface AgentEventStreamParams {
+  /** shown on message boxes in the log */
+  title: string;
+  /** where to write the JSONL transcript. nothing is recorded when omitted */
+  transcriptPath?: string | undefined;
+  /** where to write the agent's raw output records as JSONL, for replaying in tests */
+  rawTranscriptPath?: string | undefined;
+}
+
+// transcripts outlive the run (action output, test fixtures), so keep the runner's secrets out
+function transcriptLine(record: unknown): string {
+  return `${redactSecrets(JSON.stringify(record))}\n`;
+}
+
+/**
+ * Logs agent messages and tool calls, and records every event to the transcript.
+ */
+export class AgentEventStream {
+  readonly title: string;
+  readonly transcriptPath: string | undefined;
+  readonly rawTranscriptPath: string | undefined;
+
+  constructor(params: AgentEventStreamParams) {
+    this.title = params.title;
+    this.transcriptPath = params.transcriptPath;
+    this.rawTranscriptPath = params.rawTranscriptPath;
+    if (this.transcriptPath) {
+      writeFileSync(this.transcriptPath, "");
+    }
+    if (this.rawTranscriptPath) {
+      writeFileSync(this.rawTranscriptPath, "");
+    }
+  }
+
+  /** record a raw output record as the adapter received it, before it's parsed into events */
+  recordRaw(record: unknown): void {
+    if (this.rawTranscriptPath) {
+      appendFileSync(this.rawTranscriptPath, transcriptLine(record));
+    }
+  }
+
+  emit(event: AgentEvent): void {
+    if (event.type === "message") {
+      log.box(event.text, { title: this.title });
+    } else if (event.type === "tool_call") {
+      log.toolCall({ toolName: event.tool, input: event.input });
+    }
+    if (this.transcriptPath) {
+      const recorded: RecordedAgentEvent = { time: new Date().toISOString(), ...event };
+      appendFileSync(this.transcriptPath, transcriptLine(recorded));
+    }
+  }
+}
@@ -7,7 +7,8 @@ import { log } from "../utils/cli.ts";
 import { installFromGithub } from "../utils/install.ts";
 import { spawn } from "../utils/subprocess.ts";
 import { getGitHubInstallationToken } from "../utils/token.ts";
-import { type AgentRunContext, agent } from "./shared.ts";
+import type { AgentEventStream } from "./events.ts";
+import { type AgentOutputContext, type AgentRunContext, agent } from "./shared.ts";
 
 // effort configuration: model + thinking level
 // thinkingLevel is set via settings.json modelConfig.generateContentConfig.thinkingConfig
@@ -88,7 +89,7 @@ const messageHandlers = {
     // initialization event - no logging needed
     assistantMessageBuffer = "";
   },
-  message: (event: GeminiMessageEvent) => {
+  message: (event: GeminiMessageEvent, events: AgentEventStream) => {
     log.debug(JSON.stringify(event, null, 2));
     if (event.role === "assistant" && event.content?.trim()) {
       if (event.delta) {
@@ -98,38 +99,45 @@ const messageHandlers = {
         // final message - log it
         const message = event.content.trim();
         if (message) {
-          log.box(message, { title: "Gemini" });
+          events.emit({ type: "message", text: message });
         }
         assistantMessageBuffer = "";
       }
     } else if (event.role === "assistant" && !event.delta && assistantMessageBuffer.trim()) {
       // if we have buffered content and get a non-delta message, log the buffer
-      log.box(assistantMessageBuffer.trim(), { title: "Gemini" });
+      events.emit({ type: "message", text: assistantMessageBuffer.trim() });
       assistantMessageBuffer = "";
     }
   },
-  tool_use: (event: GeminiToolUseEvent) => {
+  tool_use: (event: GeminiToolUseEvent, events: AgentEventStream) => {
     log.debug(JSON.stringify(event, null, 2));
     if (event.tool_name) {
-      log.toolCall({
-        toolName: event.tool_name,
+      events.emit({
+        type: "tool_call",
+        id: event.tool_id,
+        tool: event.tool_name,
         input: event.parameters || {},
       });
     }
   },
-  tool_result: (event: GeminiToolResultEvent) => {
+  tool_result: (event: GeminiToolResultEvent, events: AgentEventStream) => {
     log.debug(JSON.stringify(event, null, 2));
+    const output = typeof event.output === "string" ? event.output : JSON.stringify(event.output);
+    events.emit({
+      type: "tool_result",
+      id: event.tool_id,
+      output: output ?? "",
+      isError: event.status === "error",
+    });
     if (event.status === "error") {
-      const errorMsg =
-        typeof event.output === "string" ? event.output : JSON.stringify(event.output);
-      log.warning(`Tool call failed: ${errorMsg}`);
+      log.warning(`Tool call failed: ${output}`);
     }
   },
-  result: async (event: GeminiResultEvent) => {
+  result: async (event: GeminiResultEvent, events: AgentEventStream) => {
     log.debug(JSON.stringify(event, null, 2));
     // log any remaining buffered assistant message
     if (assistantMessageBuffer.trim()) {
-      log.box(assistantMessageBuffer.trim(), { title: "Gemini" });
+      events.emit({ type: "message", text: assistantMessageBuffer.trim() });
       assistantMessageBuffer = "";
     }
 
@@ -191,6 +199,7 @@ export const gemini = agent({
       ctx.instructions.full,
     ];
 
+    const handleEvent = createOutputHandler(ctx);
     let finalOutput = "";
     let stdoutBuffer = "";
 
@@ -199,6 +208,7 @@ export const gemini = agent({
         cmd: "node",
         args: [cliPath, ...args],
         env: process.env,
+        signal: ctx.budget.signal,
         onStdout: async (chunk) => {
           const text = chunk.toString();
           finalOutput += text;
@@ -217,11 +227,7 @@ export const gemini = agent({
             log.debug(`[gemini stdout] ${trimmed}`);
 
             try {
-              const event = JSON.parse(trimmed) as GeminiEvent;
-              const handler = messageHandlers[event.type as keyof typeof messageHandlers];
-              if (handler) {
-                await handler(event as never);
-              }
+              await handleEvent(JSON.parse(trimmed) as GeminiEvent);
             } catch {
               // ignore parse errors - might be non-JSON output from gemini cli
               log.debug(`[gemini] non-JSON stdout line: ${trimmed.substring(0, 200)}`);
@@ -269,8 +275,26 @@ export const gemini = agent({
       };
     }
   },
+  createOutputHandler,
 });
 
+function createOutputHandler(ctx: AgentOutputContext) {
+  return async (event: GeminiEvent) => {
+    ctx.events.recordRaw(event);
+    // gemini only reports usage once, with the final result
+    if (event.type === "result" && event.stats) {
+      ctx.budget.set({
+        inputTokens: event.stats.input_tokens ?? 0,
+        outputTokens: event.stats.output_tokens ?? 0,
+      });
+    }
+    const handler = messageHandlers[event.type as keyof typeof messageHandlers];
+    if (handler) {
+      await handler(event as never, ctx.events);
+    }
+  };
+}
+
 /**
  * Configure Gemini CLI settings by writing to settings.json.
  * Returns the model to use for CLI args.
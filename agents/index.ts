@@ -4,6 +4,7 @@ import { codex } from "./codex.ts";
 import { cursor } from "./cursor.ts";
 import { gemini } from "./gemini.ts";
 import { opencode } from "./opencode.ts";
+import { scripted } from "./scripted.ts";
 import type { Agent } from "./shared.ts";
 
 export type { Agent } from "./shared.ts";
@@ -14,4 +15,5 @@ export const agents = {
   cursor,
   gemini,
   opencode,
+  scripted,
 } satisfies Record<AgentName, Agent>;
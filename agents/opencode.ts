@@ -4,6 +4,7 @@ import { ghPullfrogMcpName } from "../external.ts";
 import { log } from "../utils/cli.ts";
 import { installFromNpmTarball } from "../utils/install.ts";
 import { spawn } from "../utils/subprocess.ts";
+import type { AgentEventStream } from "./events.ts";
 import { type AgentRunContext, agent } from "./shared.ts";
 
 async function installOpencode(): Promise<string> {
@@ -68,6 +69,7 @@ export const opencode = agent({
       env,
       timeout: 600000, // 10 minutes timeout to prevent infinite hangs
       stdio: ["ignore", "pipe", "pipe"],
+      signal: ctx.budget.signal,
       onStdout: async (chunk) => {
         const text = chunk.toString();
         output += text;
@@ -87,6 +89,7 @@ export const opencode = agent({
 
           try {
             const event = JSON.parse(trimmed) as OpenCodeEvent;
+            ctx.events.recordRaw(event);
             eventCount++;
 
             // debug log all events to diagnose ordering and missing MCP/bash tool calls
@@ -104,9 +107,22 @@ export const opencode = agent({
               );
             }
             lastActivityTime = Date.now();
+            // usage and cost arrive per step, so budgets are checked as the run goes
+            if (event.type === "step_finish" && event.part?.tokens) {
+              const tokens = event.part.tokens;
+              const cacheRead = tokens.cache?.read ?? 0;
+              const cacheWrite = tokens.cache?.write ?? 0;
+              ctx.budget.add({
+                inputTokens: (tokens.input ?? 0) + cacheRead + cacheWrite,
+                outputTokens: tokens.output ?? 0,
+                cacheReadTokens: cacheRead,
+                cacheWriteTokens: cacheWrite,
+                costUsd: event.part.cost ?? null,
+              });
+            }
             const handler = messageHandlers[event.type as keyof typeof messageHandlers];
             if (handler) {
-              await handler(event as never);
+              await handler(event as never, ctx.events);
             } else {
               // log unhandled event types for visibility
               log.info(
@@ -403,11 +419,11 @@ const messageHandlers = {
       );
     }
   },
-  text: (event: OpenCodeTextEvent) => {
+  text: (event: OpenCodeTextEvent, events: AgentEventStream) => {
     // log from text events only to avoid duplicates
     if (event.part?.text?.trim()) {
       const message = event.part.text.trim();
-      log.box(message, { title: "OpenCode" });
+      events.emit({ type: "message", text: message });
       finalOutput = message;
     }
   },
@@ -438,7 +454,7 @@ const messageHandlers = {
       currentStepType = null;
     }
   },
-  tool_use: (event: OpenCodeToolUseEvent) => {
+  tool_use: (event: OpenCodeToolUseEvent, events: AgentEventStream) => {
     const toolName = event.part?.tool;
     const toolId = event.part?.callID;
     const parameters = event.part?.state?.input;
@@ -456,22 +472,33 @@ const messageHandlers = {
         stepHistory[stepHistory.length - 1].toolCalls.push(toolName);
       }
 
-      log.toolCall({
-        toolName,
-        input: parameters || {},
-      });
+      events.emit({ type: "tool_call", id: toolId, tool: toolName, input: parameters || {} });
 
       // if tool already completed (status in same event), log output
+      if (status === "completed" || status === "error") {
+        events.emit({
+          type: "tool_result",
+          id: toolId,
+          output: output ?? "",
+          isError: status === "error",
+        });
+      }
       if (status === "completed" && output) {
         log.debug(`  output: ${output}`);
       }
     }
   },
-  tool_result: (event: OpenCodeToolResultEvent) => {
+  tool_result: (event: OpenCodeToolResultEvent, events: AgentEventStream) => {
     // handle both new part structure and legacy flat structure
     const toolId = event.part?.callID || event.tool_id;
     const status = event.part?.state?.status || event.status || "unknown";
     const output = event.part?.state?.output || event.output;
+    events.emit({
+      type: "tool_result",
+      id: toolId,
+      output: typeof output === "string" ? output : JSON.stringify(output ?? ""),
+      isError: status === "error",
+    });
 
     if (toolId) {
       const toolStartTime = toolCallTimings.get(toolId);
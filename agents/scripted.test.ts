@@ -0,0 +1,40 @@
+import { mkdtempSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { loadAgentScript } from './scripted.ts';
+
+describe('loadAgentScript', () => {
+  let dir: string;
+
+  beforeEach(() => {
+    dir = mkdtempSync(join(tmpdir(), 'pullfrog-scripted-test-'));
+  });
+
+  afterEach(() => {
+    rmSync(dir, { recursive: true, force: true });
+  });
+
+  function write(name: string, source: string): string {
+    const path = join(dir, name);
+    writeFileSync(path, source);
+    return path;
+  }
+
+  it('reads YAML and JSON scripts', () => {
+    const expected = {
+      steps: [{ tool: 'select_mode', args: { modeName: 'Build' } }, { tool: 'push_branch', expectError: true }, { message: 'Done.' }],
+    };
+    const yaml = write(
+      'script.yml',
+      'steps:\n  - tool: select_mode\n    args:\n      modeName: Build\n  - tool: push_branch\n    expectError: true\n  - message: Done.\n'
+    );
+    expect(loadAgentScript(yaml)).toEqual(expected);
+    expect(loadAgentScript(write('script.json', JSON.stringify(expected)))).toEqual(expected);
+  });
+
+  it('rejects steps it does not understand', () => {
+    const path = write('script.yml', 'steps:\n  - tool: select_mode\n    arguments:\n      modeName: Build\n');
+    expect(() => loadAgentScript(path)).toThrow(`agent script ${path} is invalid`);
+    expect(() => loadAgentScript(write('broken.json', '{"steps": ['))).toThrow(/could not be parsed/);
+  });
+});
@@ -0,0 +1,147 @@
+import { readFileSync } from "node:fs";
+import { extname, resolve } from "node:path";
+import { type } from "arktype";
+import { parse as parseYaml } from "yaml";
+import { ghPullfrogMcpName } from "../external.ts";
+import { McpClient, toolResultText } from "../mcp/client.ts";
+import type { ToolResult } from "../mcp/shared.ts";
+import { log } from "../utils/cli.ts";
+import { type AgentOutputContext, agent } from "./shared.ts";
+
+/**
+ * A stand-in for a model: calls gh_pullfrog tools from a YAML or JSON script, in order.
+ * Lets main() run end-to-end in tests against a mock GitHub, e.g.
+ *
+ *   steps:
+ *     - tool: select_mode
+ *       args:
+ *         modeName: Build
+ *     - tool: push_branch
+ *       expectError: true
+ *     - message: Done.
+ *
+ * A step that fails (or succeeds when `expectError` is set) stops the run and fails it.
+ */
+
+const ScriptStepSchema = type({
+  "+": "reject",
+  tool: "string > 0",
+  "args?": "Record<string, unknown>",
+  "expectError?": "boolean",
+}).or({
+  "+": "reject",
+  message: "string",
+});
+
+export const AgentScriptSchema = type({
+  "+": "reject",
+  steps: ScriptStepSchema.array(),
+});
+
+export type AgentScript = typeof AgentScriptSchema.infer;
+
+/** read a script from a .json file, or any other file as YAML */
+export function loadAgentScript(path: string): AgentScript {
+  const source = readFileSync(resolve(path), "utf-8");
+  let parsed: unknown;
+  try {
+    parsed = extname(path) === ".json" ? JSON.parse(source) : parseYaml(source);
+  } catch (error) {
+    throw new Error(
+      `agent script ${path} could not be parsed: ${error instanceof Error ? error.message : String(error)}`
+    );
+  }
+  const validated = AgentScriptSchema(parsed);
+  if (validated instanceof type.errors) {
+    throw new Error(`agent script ${path} is invalid: ${validated.summary}`);
+  }
+  return validated;
+}
+
+// raw output records, as written to PULLFROG_RAW_TRANSCRIPT
+type ScriptedRecord =
+  | { type: "message"; text: string }
+  | { type: "tool_call"; id: string; tool: string; args: Record<string, unknown> }
+  | { type: "tool_result"; id: string; result: ToolResult };
+
+export const scripted = agent({
+  name: "scripted",
+  // nothing to install: the script is run in-process
+  install: async () => "",
+  run: async (ctx) => {
+    const scriptPath = process.env.PULLFROG_AGENT_SCRIPT;
+    if (!scriptPath) {
+      throw new Error("PULLFROG_AGENT_SCRIPT is required for scripted agent");
+    }
+    const script = loadAgentScript(scriptPath);
+    log.info(`» running ${script.steps.length} scripted steps from ${scriptPath}`);
+
+    const handleRecord = createOutputHandler(ctx);
+    const client = new McpClient(ctx.mcpServerUrl);
+    let output = "";
+
+    try {
+      await client.initialize();
+      for (const [index, step] of script.steps.entries()) {
+        // only the time limit applies, since a script reports no usage
+        if (ctx.budget.signal.aborted) {
+          return { success: false, output };
+        }
+        if ("message" in step) {
+          handleRecord({ type: "message", text: step.message });
+          output = step.message;
+          continue;
+        }
+
+        const id = `step_${index + 1}`;
+        const args = step.args ?? {};
+        handleRecord({ type: "tool_call", id, tool: step.tool, args });
+        const result = await client.callTool(step.tool, args);
+        handleRecord({ type: "tool_result", id, result });
+
+        const expectError = step.expectError ?? false;
+        if ((result.isError ?? false) !== expectError) {
+          const error = expectError
+            ? `step ${index + 1} (${step.tool}) was expected to fail but succeeded`
+            : `step ${index + 1} (${step.tool}) failed: ${toolResultText(result)}`;
+          log.error(error);
+          return { success: false, error, output };
+        }
+      }
+
+      log.info("» script completed successfully");
+      return { success: true, output };
+    } catch (error) {
+      const errorMessage = error instanceof Error ? error.message : String(error);
+      log.error(`Failed to run agent script: ${errorMessage}`);
+      return { success: false, error: errorMessage, output };
+    } finally {
+      await client.close();
+    }
+  },
+  createOutputHandler,
+});
+
+function createOutputHandler(ctx: AgentOutputContext) {
+  return (record: ScriptedRecord) => {
+    ctx.events.recordRaw(record);
+    if (record.type === "message") {
+      ctx.events.emit({ type: "message", text: record.text });
+    } else if (record.type === "tool_call") {
+      ctx.events.emit({
+        type: "tool_call",
+        id: record.id,
+        tool: record.tool,
+        input: record.args,
+        server: ghPullfrogMcpName,
+      });
+    } else {
+      const output = toolResultText(record.result);
+      const isError = record.result.isError ?? false;
+      ctx.events.emit({ type: "tool_result", id: record.id, output, isError });
+      if (isError) {
+        log.warning(`Tool error: ${output}`);
+      }
+    }
+  };
+}
@@ -1,8 +1,10 @@
 import type { show } from "@ark/util";
 import { type AgentManifest, type AgentName, agentsManifest } from "../external.ts";
+import type { AgentUsage, RunBudgetTracker } from "../utils/budget.ts";
 import { log } from "../utils/cli.ts";
 import type { ResolvedInstructions } from "../utils/instructions.ts";
 import type { ResolvedPayload } from "../utils/payload.ts";
+import type { AgentEventStream } from "./events.ts";
 
 /**
  * Result returned by agent execution
@@ -11,7 +13,10 @@ export interface AgentResult {
   success: boolean;
   output?: string | undefined;
   error?: string | undefined;
-  metadata?: Record<string, unknown>;
+  metadata?: {
+    usage?: AgentUsage;
+    [key: string]: unknown;
+  };
 }
 
 /**
@@ -22,6 +27,10 @@ export interface AgentRunContext {
   mcpServerUrl: string;
   tmpdir: string;
   instructions: ResolvedInstructions;
+  /** usage limits for the run; agents report usage to it and stop when its signal aborts */
+  budget: RunBudgetTracker;
+  /** adapters emit messages and tool activity here; it logs them and records the transcript */
+  events: AgentEventStream;
 }
 
 export const agent = <const input extends AgentInput>(input: input): defineAgent<input> => {
@@ -37,16 +46,37 @@ export const agent = <const input extends AgentInput>(input: input): defineAgent
         title: "Instructions",
       });
       log.info(`» tool permissions: web=${web}, search=${search}, write=${write}, bash=${bash}`);
-      return input.run(ctx);
+      const runResult = await input.run(ctx);
+      const usage = ctx.budget.usage();
+      const result: AgentResult = ctx.budget.exceededReason
+        ? { ...runResult, success: false, error: `Budget exceeded: ${ctx.budget.exceededReason}` }
+        : runResult;
+      ctx.events.emit({ type: "usage", usage });
+      if (!result.success) {
+        ctx.events.emit({ type: "error", message: result.error || "Agent execution failed" });
+      }
+      return { ...result, metadata: { ...result.metadata, usage } };
     },
     ...agentsManifest[input.name],
   } as never;
 };
 
+/** the parts of the run context an adapter's output handler uses */
+export type AgentOutputContext = Pick<AgentRunContext, "events" | "budget">;
+
+/** handles one raw output record: an SDK message, or a parsed line of the CLI's JSON output */
+export type AgentOutputHandler = (record: any) => void | Promise<void>;
+
 export interface AgentInput {
   name: AgentName;
   install: (token?: string) => Promise<string>;
   run: (ctx: AgentRunContext) => Promise<AgentResult>;
+  /**
+   * builds a fresh handler for the agent's raw output.
+   * run() feeds it live output; the replay harness feeds it recorded output.
+   * adapters that still parse inline in run() leave it out and can't be replayed yet.
+   */
+  createOutputHandler?: (ctx: AgentOutputContext) => AgentOutputHandler;
 }
 
 export interface Agent extends AgentInput, AgentManifest {}
@@ -14,6 +14,10 @@ export interface AgentManifest {
   /** empty array means accepts any *API_KEY* env var */
   apiKeyNames: string[];
   url: string;
+  /** whether the agent reports the run's cost, which max_usd needs */
+  reportsCost: boolean;
+  /** true for agents that don't call a model: they need no API key and only run when named */
+  keyless?: boolean;
 }
 
 // agent manifest - static metadata about available agents
@@ -22,26 +26,40 @@ export const agentsManifest = {
     displayName: "Claude Code",
     apiKeyNames: ["ANTHROPIC_API_KEY"],
     url: "https://claude.com/claude-code",
+    reportsCost: true,
   },
   codex: {
     displayName: "Codex CLI",
     apiKeyNames: ["OPENAI_API_KEY"],
     url: "https://platform.openai.com/docs/guides/codex",
+    reportsCost: false,
   },
   cursor: {
     displayName: "Cursor CLI",
     apiKeyNames: ["CURSOR_API_KEY"],
     url: "https://cursor.com/",
+    reportsCost: false,
   },
   gemini: {
     displayName: "Gemini CLI",
     apiKeyNames: ["GOOGLE_API_KEY", "GEMINI_API_KEY"],
     url: "https://ai.google.dev/gemini-api/docs",
+    reportsCost: false,
   },
   opencode: {
     displayName: "OpenCode",
     apiKeyNames: [],
     url: "https://opencode.ai",
+    reportsCost: true,
+  },
+  // runs a fixed script of MCP tool calls instead of a model, for end-to-end tests.
+  // select it with the agent input or AGENT_OVERRIDE and name the script with PULLFROG_AGENT_SCRIPT
+  scripted: {
+    displayName: "Scripted",
+    apiKeyNames: [],
+    url: "https://github.com/pullfrog/pullfrog",
+    reportsCost: false,
+    keyless: true,
   },
 } as const satisfies Record<string, AgentManifest>;
 
@@ -59,6 +77,8 @@ export type Effort = typeof Effort.infer;
 // tool permission types shared with server dispatch
 export type ToolPermission = "disabled" | "enabled";
 export type BashPermission = "disabled" | "restricted" | "enabled";
+// sandbox for restricted bash: "isolated" adds network/filesystem isolation on top of env filtering
+export type SandboxMode = "standard" | "isolated";
 
 // permission level for the author who triggered the event
 // matches GitHub's permission levels: admin > write > maintain > triage > read > none
@@ -0,0 +1,147 @@
+import { generateKeyPairSync } from 'node:crypto';
+import { mkdtempSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import type { PayloadEvent } from './external.ts';
+import { main } from './main.ts';
+import { MockGitHub } from './test/mockGitHub.ts';
+import { $ } from './utils/shell.ts';
+
+// main() mints its installation token from a GitHub App in self-hosted mode; the mock accepts any key
+const { privateKey } = generateKeyPairSync('rsa', {
+  modulusLength: 2048,
+  publicKeyEncoding: { type: 'spki', format: 'pem' },
+  privateKeyEncoding: { type: 'pkcs8', format: 'pem' },
+});
+
+describe('main() with the scripted agent', { timeout: 60_000 }, () => {
+  let github: MockGitHub;
+  let tmp: string;
+  let workdir: string;
+  const cwd = process.cwd();
+
+  beforeEach(async () => {
+    github = await MockGitHub.start({ files: { 'src/cli.ts': 'export const flags = [];\n' } });
+    tmp = mkdtempSync(join(tmpdir(), 'pullfrog-main-test-'));
+    workdir = join(tmp, 'repo');
+    // stands in for actions/checkout
+    $('git', ['clone', '--quiet', github.remotePath, workdir], { log: false });
+  });
+
+  afterEach(async () => {
+    process.chdir(cwd);
+    vi.unstubAllEnvs();
+    await github.close();
+    rmSync(tmp, { recursive: true, force: true });
+  });
+
+  function runMain(params: { event: PayloadEvent; script: string }) {
+    const scriptPath = join(tmp, 'script.yml');
+    writeFileSync(scriptPath, params.script);
+    const env: Record<string, string | undefined> = {
+      GITHUB_API_URL: github.url,
+      GITHUB_SERVER_URL: github.url,
+      GITHUB_REPOSITORY: github.fullName,
+      GITHUB_WORKSPACE: workdir,
+      GITHUB_OUTPUT: join(tmp, 'output'),
+      GITHUB_STEP_SUMMARY: join(tmp, 'summary'),
+      GITHUB_RUN_ID: undefined,
+      GITHUB_JOB: undefined,
+      PULLFROG_SELF_HOSTED: 'true',
+      GITHUB_APP_ID: '1',
+      GITHUB_PRIVATE_KEY: privateKey,
+      PULLFROG_AGENT_SCRIPT: scriptPath,
+      PULLFROG_RAW_TRANSCRIPT: undefined,
+      AGENT_OVERRIDE: undefined,
+      INPUT_PROMPT: JSON.stringify({ '~pullfrog': true, agent: 'scripted', prompt: 'Add a --dry-run flag', event: params.event }),
+      // main() sets these, so stub them to have them restored
+      GITHUB_TOKEN: process.env.GITHUB_TOKEN,
+      ORIGINAL_GITHUB_TOKEN: process.env.ORIGINAL_GITHUB_TOKEN,
+      PULLFROG_TEMP_DIR: process.env.PULLFROG_TEMP_DIR,
+    };
+    for (const [name, value] of Object.entries(env)) vi.stubEnv(name, value);
+    return main();
+  }
+
+  it('turns an issue into a pull request', async () => {
+    const issue = github.createIssue({ title: 'Add a --dry-run flag' });
+    const result = await runMain({
+      event: { trigger: 'issues_opened', issue_number: issue.number, issue_title: issue.title, issue_body: null, authorPermission: 'write' },
+      script: `steps:
+  - tool: select_mode
+    args:
+      modeName: Build
+  - tool: report_progress
+    args:
+      body: Adding the flag.
+  - tool: create_branch
+    args:
+      branchName: pullfrog/dry-run
+  - tool: bash
+    args:
+      command: echo "- add --dry-run" > CHANGELOG.md
+      description: Add a changelog entry
+  - tool: commit_files
+    args:
+      message: Add changelog entry
+      files: [CHANGELOG.md]
+  - tool: push_branch
+  - tool: create_pull_request
+    args:
+      title: Add --dry-run
+      body: Adds the flag.
+      base: main
+  - tool: report_progress
+    args:
+      body: Opened a pull request.
+  - message: Done.
+`,
+    });
+
+    expect(result).toMatchObject({ success: true, output: 'Done.' });
+    expect(github.readFile('pullfrog/dry-run', 'CHANGELOG.md')).toBe('- add --dry-run');
+    const pull = [...github.issues.values()].find((candidate) => 'head' in candidate);
+    expect(pull).toMatchObject({ title: 'Add --dry-run', head: 'pullfrog/dry-run', base: 'main' });
+    const comments = github.commentsOn(issue.number);
+    expect(comments).toHaveLength(1);
+    expect(comments[0]!.body).toMatch(/^Opened a pull request\./);
+    expect(github.requests).toContainEqual(expect.objectContaining({ method: 'DELETE', path: '/installation/token' }));
+  });
+
+  it('checks out an opened pull request and reviews it in Review mode', async () => {
+    github.pushBranch('dry-run', { 'src/cli.ts': "export const flags = ['--dry-run'];\n" });
+    const pull = github.createPull({ title: 'Add --dry-run', head: 'dry-run' });
+    const result = await runMain({
+      event: { trigger: 'pull_request_opened', issue_number: pull.number, is_pr: true, pr_title: pull.title, pr_body: null, branch: 'dry-run' },
+      script: `steps:
+  - tool: push_branch
+    expectError: true
+  - tool: create_pull_request_review
+    args:
+      pull_number: ${pull.number}
+      body: Looks good.
+`,
+    });
+
+    expect(result.success).toBe(true);
+    expect($('git', ['rev-parse', '--abbrev-ref', 'HEAD'], { cwd: workdir, log: false })).toBe(`pr-${pull.number}`);
+    expect(github.reviews).toHaveLength(1);
+    expect(github.reviews[0]!.body).toMatch(/^Looks good\./);
+    expect(github.branchSha('dry-run')).toBe(github.reviews[0]!.commitId);
+  });
+
+  it('fails the run when a scripted tool call fails', async () => {
+    const result = await runMain({
+      event: { trigger: 'unknown' },
+      script: `steps:
+  - tool: checkout_pr
+    args:
+      pull_number: 99
+  - message: unreachable
+`,
+    });
+
+    expect(result.success).toBe(false);
+    expect(result.error).toMatch(/^step 1 \(checkout_pr\) failed: Error: /);
+  });
+});
@@ -1,12 +1,19 @@
-import { ensureProgressCommentUpdated } from "./mcp/comment.ts";
-import { initToolState, startMcpHttpServer } from "./mcp/server.ts";
-import { computeModes } from "./modes.ts";
+import { join } from "node:path";
+import * as core from "@actions/core";
+import { AgentEventStream } from "./agents/events.ts";
+import { ensureProgressCommentUpdated, reportBudgetCutoff } from "./mcp/comment.ts";
+import { applySelectedMode } from "./mcp/selectMode.ts";
+import { initToolState, startMcpHttpServer, type ToolContext } from "./mcp/server.ts";
+import { formatOutboundIncidents } from "./mcp/shared.ts";
+import { applyModeSettings, computeModes } from "./modes.ts";
 import { resolveAgent } from "./utils/agent.ts";
 import { validateApiKey } from "./utils/apiKeys.ts";
+import { RunBudgetTracker, warnIfCostLimitUnenforced } from "./utils/budget.ts";
 import { log, writeSummary } from "./utils/cli.ts";
 import { reportErrorToComment } from "./utils/errorReport.ts";
 import { createOctokit } from "./utils/github.ts";
 import { resolveInstructions } from "./utils/instructions.ts";
+import { resolveRoutedMode } from "./utils/modeRouting.ts";
 import { normalizeEnv } from "./utils/normalizeEnv.ts";
 import { resolvePayload } from "./utils/payload.ts";
 import { resolveRepoData } from "./utils/repoData.ts";
@@ -45,7 +52,37 @@ export async function main(): Promise<MainResult> {
 
     // resolve payload after repoData so permissions can use DB settings
     // precedence: action inputs > json payload > repoSettings > fallbacks
-    const payload = resolvePayload(repo.repoSettings);
+    const resolvedPayload = resolvePayload(repo.repoSettings);
+    // run_checks and dependency installation are only registered when bash isn't disabled
+    const commandTools = resolvedPayload.bash !== "disabled";
+    const modes = [
+      ...computeModes({ runChecks: commandTools, dependencyInstallation: commandTools }),
+      ...repo.repoSettings.modes,
+    ];
+
+    // when the event determines the mode, pick it up front so its permissions and effort
+    // apply to the whole run and the agent can skip select_mode
+    const routedMode = await resolveRoutedMode({
+      event: resolvedPayload.event,
+      prompt: resolvedPayload.prompt,
+      modes,
+      routes: repo.repoSettings.modeRoutes,
+      fetchLabels: async () => {
+        const issueNumber = resolvedPayload.event.issue_number;
+        if (issueNumber === undefined) return [];
+        const response = await octokit.issues.listLabelsOnIssue({
+          owner: repo.owner,
+          repo: repo.name,
+          issue_number: issueNumber,
+          per_page: 100,
+        });
+        return response.data.map((label) => label.name);
+      },
+    });
+    if (routedMode) {
+      log.info(`» routed to ${routedMode.name} mode`);
+    }
+    const payload = routedMode ? applyModeSettings(resolvedPayload, routedMode) : resolvedPayload;
     if (payload.cwd && process.cwd() !== payload.cwd) {
       process.chdir(payload.cwd);
     }
@@ -70,9 +107,7 @@ export async function main(): Promise<MainResult> {
     });
     timer.checkpoint("git");
 
-    const modes = [...computeModes(), ...repo.repoSettings.modes];
-
-    await using mcpHttpServer = await startMcpHttpServer({
+    const toolContext: ToolContext = {
       repo,
       payload,
       octokit,
@@ -82,26 +117,58 @@ export async function main(): Promise<MainResult> {
       toolState,
       runId: runInfo.runId,
       jobId: runInfo.jobId,
-    });
+    };
+    await using mcpHttpServer = await startMcpHttpServer(toolContext);
     log.info(`» MCP server started at ${mcpHttpServer.url}`);
+    if (routedMode) {
+      applySelectedMode(toolContext, routedMode);
+    }
     timer.checkpoint("mcpServer");
 
     const instructions = resolveInstructions({
       payload,
       repoData: repo,
       modes,
+      selectedMode: routedMode,
     });
 
+    warnIfCostLimitUnenforced(payload.budget, agent);
+    const budget = new RunBudgetTracker(payload.budget);
+    const events = new AgentEventStream({
+      title: agent.displayName,
+      transcriptPath: join(tmpdir, "transcript.jsonl"),
+      // set PULLFROG_RAW_TRANSCRIPT to a path to record the agent's raw output for test/replay.ts
+      rawTranscriptPath: process.env.PULLFROG_RAW_TRANSCRIPT || undefined,
+    });
+    // exposed so workflows can upload the transcript as an artifact
+    core.setOutput("transcript", events.transcriptPath);
     const result = await agent.run({
       payload,
       mcpServerUrl: mcpHttpServer.url,
       tmpdir,
       instructions,
+      budget,
+      events,
     });
+    budget.dispose();
+
+    if (budget.exceededReason) {
+      try {
+        await reportBudgetCutoff(toolContext, budget.exceededReason);
+      } catch {
+        // the failed result below still reports the cutoff
+      }
+    }
 
-    // write last progress body to job summary
-    if (toolState.lastProgressBody) {
-      writeSummary(toolState.lastProgressBody);
+    // write last progress body and any secret filter incidents to job summary
+    const summaryParts = [
+      toolState.lastProgressBody,
+      toolState.outboundIncidents?.length
+        ? formatOutboundIncidents(toolState.outboundIncidents)
+        : undefined,
+    ].filter((part) => part !== undefined && part !== "");
+    if (summaryParts.length > 0) {
+      writeSummary(summaryParts.join("\n\n"));
     }
 
     const mainResult = await handleAgentResult(result);
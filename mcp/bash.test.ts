@@ -0,0 +1,44 @@
+import { mkdtempSync, readFileSync, rmSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { limitOutput } from './bash.ts';
+
+describe('limitOutput', () => {
+  let tempDir: string;
+
+  beforeEach(() => {
+    tempDir = mkdtempSync(join(tmpdir(), 'pullfrog-bash-test-'));
+    vi.stubEnv('PULLFROG_TEMP_DIR', tempDir);
+  });
+
+  afterEach(() => {
+    vi.unstubAllEnvs();
+    rmSync(tempDir, { recursive: true, force: true });
+  });
+
+  const numbered = (count: number) => Array.from({ length: count }, (_, i) => `line ${i + 1}`).join('\n');
+
+  it('returns short output unchanged', () => {
+    expect(limitOutput(numbered(5), { headLines: 2, tailLines: 3 })).toEqual({ output: numbered(5) });
+  });
+
+  it('keeps head and tail lines and spills the full output to a file', () => {
+    const result = limitOutput(numbered(10), { headLines: 2, tailLines: 3 });
+    expect(result.output.split('\n')).toEqual([
+      'line 1',
+      'line 2',
+      `[... 5 lines omitted, full output in ${result.outputPath} ...]`,
+      'line 8',
+      'line 9',
+      'line 10',
+    ]);
+    expect(readFileSync(result.outputPath!, 'utf-8')).toBe(numbered(10));
+  });
+
+  it('redacts secrets in both the inline and the spilled output', () => {
+    vi.stubEnv('ANTHROPIC_API_KEY', 'sk-super-secret-value');
+    const result = limitOutput(`key=sk-super-secret-value\n${numbered(10)}`, { headLines: 1, tailLines: 1 });
+    expect(result.output).toContain('key=[REDACTED_SECRET]');
+    expect(readFileSync(result.outputPath!, 'utf-8')).not.toContain('sk-super-secret-value');
+  });
+});
@@ -1,15 +1,92 @@
 import { type ChildProcess, spawn } from "node:child_process";
+import { writeFileSync } from "node:fs";
+import { join } from "node:path";
 import { type } from "arktype";
+import { log } from "../utils/cli.ts";
+import type { ResolvedPayload } from "../utils/payload.ts";
+import { redactSecrets } from "../utils/secrets.ts";
+import {
+  commitsTouching,
+  diffWorkingTree,
+  findWritePolicyViolations,
+  formatWritePolicyViolations,
+  resolveWritePolicy,
+  revertPaths,
+  snapshotWorkingTree,
+  type WorkingTreeSnapshot,
+  WritePolicyError,
+} from "../utils/writePolicy.ts";
+import { DEFAULT_ALLOWED_HOSTS, NetworkSandbox } from "./sandbox.ts";
 import type { ToolContext } from "./server.ts";
 import { execute, tool } from "./shared.ts";
 
+const DEFAULT_HEAD_LINES = 50;
+const DEFAULT_TAIL_LINES = 150;
+
 export const BashParams = type({
   command: "string",
   description: "string",
   "timeout?": "number",
   "working_directory?": "string",
+  head_lines: type.number
+    .describe(`Lines to keep from the start of long output (default ${DEFAULT_HEAD_LINES})`)
+    .default(DEFAULT_HEAD_LINES),
+  tail_lines: type.number
+    .describe(`Lines to keep from the end of long output (default ${DEFAULT_TAIL_LINES})`)
+    .default(DEFAULT_TAIL_LINES),
 });
 
+// hard cap on inline output regardless of line limits (minified files, progress bars, etc.)
+const MAX_INLINE_OUTPUT_CHARS = 30000;
+
+let spilledOutputCount = 0;
+
+export interface LimitedOutput {
+  output: string;
+  /** full (redacted) output, only set when output was truncated */
+  outputPath?: string;
+}
+
+/**
+ * redact secrets and truncate long output to its first and last lines.
+ * when truncated, the full redacted output is written to PULLFROG_TEMP_DIR and its path returned.
+ */
+export function limitOutput(
+  rawOutput: string,
+  limits: { headLines: number; tailLines: number }
+): LimitedOutput {
+  const output = redactSecrets(rawOutput);
+  const lines = output.split("\n");
+  const headLines = Math.max(0, Math.floor(limits.headLines));
+  const tailLines = Math.max(0, Math.floor(limits.tailLines));
+  if (lines.length <= headLines + tailLines && output.length <= MAX_INLINE_OUTPUT_CHARS) {
+    return { output };
+  }
+
+  let outputPath: string | undefined;
+  const tempDir = process.env.PULLFROG_TEMP_DIR;
+  if (tempDir) {
+    spilledOutputCount++;
+    outputPath = join(tempDir, `bash-output-${spilledOutputCount}.log`);
+    writeFileSync(outputPath, output);
+  }
+  const where = outputPath ? `, full output in ${outputPath}` : "";
+
+  let truncated = output;
+  const omittedLines = lines.length - headLines - tailLines;
+  if (omittedLines > 0) {
+    const head = lines.slice(0, headLines);
+    const tail = lines.slice(lines.length - tailLines);
+    truncated = [...head, `[... ${omittedLines} lines omitted${where} ...]`, ...tail].join("\n");
+  }
+  if (truncated.length > MAX_INLINE_OUTPUT_CHARS) {
+    const half = MAX_INLINE_OUTPUT_CHARS / 2;
+    truncated = `${truncated.slice(0, half)}\n[... output truncated${where} ...]\n${truncated.slice(-half)}`;
+  }
+
+  return outputPath ? { output: truncated, outputPath } : { output: truncated };
+}
+
 // patterns for sensitive env vars: suffixes (_KEY, _SECRET, _TOKEN) plus AI provider prefixes
 const SENSITIVE_PATTERNS = [/_KEY$/i, /_SECRET$/i, /_TOKEN$/i, /_PASSWORD$/i, /_CREDENTIAL$/i];
 
@@ -34,16 +111,52 @@ function filterEnv(isPublicRepo: boolean): Record<string, string> {
   return filtered;
 }
 
+export interface SandboxOptions {
+  isPublicRepo: boolean;
+  /** set when restricted bash runs in the network-isolated sandbox */
+  network?: NetworkSandbox | undefined;
+}
+
+/** whether commands from the bash tools run in the network-isolated sandbox */
+export function usesNetworkSandbox(payload: ResolvedPayload): boolean {
+  return payload.bash === "restricted" && payload.sandbox === "isolated";
+}
+
+/**
+ * resolve how commands from the bash tools are sandboxed for this run.
+ * the isolated sandbox is opt-in and only applies to restricted bash.
+ */
+export async function resolveSandboxOptions(ctx: ToolContext): Promise<SandboxOptions> {
+  const isPublicRepo = !ctx.repo.repo.private;
+  if (!usesNetworkSandbox(ctx.payload)) {
+    return { isPublicRepo };
+  }
+  // started once per run and shared by all bash tools
+  ctx.toolState.networkSandbox ??= NetworkSandbox.start([
+    ...DEFAULT_ALLOWED_HOSTS,
+    ...ctx.payload.allowedHosts,
+  ]);
+  return { isPublicRepo, network: await ctx.toolState.networkSandbox };
+}
+
 /**
  * spawn command with filtered env. in CI, also use PID namespace isolation
- * to prevent child from reading /proc/$PPID/environ (only for public repos)
+ * to prevent child from reading /proc/$PPID/environ (only for public repos).
+ * with a network sandbox, the command runs under bubblewrap instead.
  */
 function spawnSandboxed(
   command: string,
-  options: { env: Record<string, string>; cwd: string; isPublicRepo: boolean }
+  options: { env: Record<string, string>; cwd: string } & SandboxOptions
 ): ChildProcess {
   const stdio: ["ignore", "pipe", "pipe"] = ["ignore", "pipe", "pipe"];
   const spawnOpts = { env: options.env, cwd: options.cwd, stdio, detached: true };
+  if (options.network) {
+    const { cmd, args } = options.network.wrap(command, {
+      workspace: process.cwd(),
+      cwd: options.cwd,
+    });
+    return spawn(cmd, args, spawnOpts);
+  }
   // only use PID namespace isolation for public repos in CI
   const useNamespaceIsolation = process.env.CI === "true" && options.isPublicRepo;
   return useNamespaceIsolation
@@ -67,6 +180,114 @@ async function killProcessGroup(proc: ChildProcess): Promise<void> {
   }
 }
 
+export interface SandboxedCommandResult {
+  /** combined stdout + stderr, trimmed */
+  output: string;
+  exitCode: number;
+  timedOut: boolean;
+}
+
+/**
+ * run a command with the same env filtering and isolation as the bash tool, killing it on timeout
+ */
+export async function runSandboxedCommand(
+  command: string,
+  options: { cwd: string; timeout: number } & SandboxOptions
+): Promise<SandboxedCommandResult> {
+  const { timeout, isPublicRepo } = options;
+  const proc = spawnSandboxed(command, {
+    env: filterEnv(isPublicRepo),
+    cwd: options.cwd,
+    isPublicRepo,
+    network: options.network,
+  });
+
+  let stdout = "",
+    stderr = "",
+    timedOut = false,
+    exited = false;
+  proc.stdout?.on("data", (chunk: Buffer) => {
+    stdout += chunk.toString();
+  });
+  proc.stderr?.on("data", (chunk: Buffer) => {
+    stderr += chunk.toString();
+  });
+
+  const timeoutId = setTimeout(async () => {
+    if (!exited) {
+      timedOut = true;
+      await killProcessGroup(proc);
+    }
+  }, timeout);
+
+  const exitCode = await new Promise<number | null>((resolve) => {
+    const done = (code: number | null) => {
+      exited = true;
+      clearTimeout(timeoutId);
+      resolve(code);
+    };
+    proc.on("exit", done);
+    proc.on("error", () => done(null));
+  });
+
+  let output = stderr ? (stdout ? `${stdout}\n${stderr}` : stderr) : stdout;
+  if (timedOut)
+    output = output
+      ? `${output}\n[timed out after ${timeout}ms]`
+      : `[timed out after ${timeout}ms]`;
+
+  return {
+    output: output.trim(),
+    exitCode: exitCode ?? (timedOut ? 124 : -1),
+    timedOut,
+  };
+}
+
+/**
+ * snapshot the working tree before a command when the write policy denies any paths
+ * (null when there is nothing to enforce or cwd is not a git checkout)
+ */
+export function snapshotForWritePolicy(ctx: ToolContext): WorkingTreeSnapshot | null {
+  const policy = resolveWritePolicy(ctx.payload, ctx.repo.repoSettings);
+  if (policy.deny.length === 0) return null;
+  try {
+    return snapshotWorkingTree();
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * revert uncommitted changes a command made to denied paths and throw a WritePolicyError listing
+ * them. commits are never rewritten: HEAD may have moved for other reasons (a commit_files call
+ * during a background process), so commits touching denied paths are reported instead, and
+ * push_branch and create_pull_request refuse them.
+ * `finished` describes how the command ended, e.g. "Command finished with exit code 1"
+ */
+export function enforceWritePolicy(
+  ctx: ToolContext,
+  before: WorkingTreeSnapshot,
+  finished: string
+): void {
+  const policy = resolveWritePolicy(ctx.payload, ctx.repo.repoSettings);
+  const { paths, committed } = diffWorkingTree(before);
+  const violations = findWritePolicyViolations(paths, policy);
+  if (violations.length === 0) return;
+
+  const violatingPaths = violations.map((v) => v.path);
+  revertPaths(violatingPaths, before);
+  const commits = committed ? commitsTouching(before.head, violatingPaths) : [];
+  throw new WritePolicyError(
+    `${finished}, but it modified paths protected by the repository write policy. ` +
+      `Uncommitted changes to them were reverted:\n${formatWritePolicyViolations(violations)}` +
+      (commits.length > 0
+        ? "\n\nThese commits change them and were left in place. push_branch and " +
+          "create_pull_request refuse them until the changes are reverted in a new commit:\n" +
+          commits.map((commit) => `  - ${commit}`).join("\n")
+        : "")
+  );
+}
+
 export function BashTool(ctx: ToolContext) {
   const isPublicRepo = !ctx.repo.repo.private;
 
@@ -79,55 +300,250 @@ Use this tool to:
 - Execute build tools (npm, pnpm, cargo, make, etc.)
 - Run tests and linters
 - Perform git operations
-- Run shell commands in a secure environment. Unlike the built-in bash tool, this tool filters sensitive environment variables from the subprocess's environment to avoid leaking secrets.`,
+- Run shell commands in a secure environment. Unlike the built-in bash tool, this tool filters sensitive environment variables from the subprocess's environment to avoid leaking secrets.
+
+Long output is truncated to its first head_lines and last tail_lines. The full output is then saved to a file returned as outputPath - search it with grep instead of re-running the command.`,
     parameters: BashParams,
     execute: execute(async (params) => {
       const timeout = Math.min(params.timeout ?? 120000, 600000);
-      const cwd = params.working_directory ?? process.cwd();
-      const proc = spawnSandboxed(params.command, {
-        env: filterEnv(isPublicRepo),
-        cwd,
-        isPublicRepo,
+      const before = snapshotForWritePolicy(ctx);
+      const result = await runSandboxedCommand(params.command, {
+        cwd: params.working_directory ?? process.cwd(),
+        timeout,
+        ...(await resolveSandboxOptions(ctx)),
       });
+      if (before) {
+        enforceWritePolicy(ctx, before, `Command finished with exit code ${result.exitCode}`);
+      }
 
-      let stdout = "",
-        stderr = "",
-        timedOut = false,
-        exited = false;
-      proc.stdout?.on("data", (chunk: Buffer) => {
-        stdout += chunk.toString();
+      const { output, outputPath } = limitOutput(result.output, {
+        headLines: params.head_lines,
+        tailLines: params.tail_lines,
       });
-      proc.stderr?.on("data", (chunk: Buffer) => {
-        stderr += chunk.toString();
+
+      return {
+        output,
+        exit_code: result.exitCode,
+        timed_out: result.timedOut,
+        ...(outputPath ? { outputPath } : {}),
+      };
+    }),
+  });
+}
+
+// background output beyond this is dropped from the front (offsets keep counting)
+const MAX_BACKGROUND_BUFFER_CHARS = 1_000_000;
+const MAX_READ_WAIT_MS = 30000;
+
+export interface BackgroundProcess {
+  handle: string;
+  command: string;
+  proc: ChildProcess;
+  /** interleaved stdout/stderr, minus anything dropped from the front */
+  output: string;
+  /** number of characters dropped from the front of output */
+  dropped: number;
+  /** absolute offset up to which output has been returned by bash_read_output */
+  readOffset: number;
+  exitCode: number | null;
+  exited: boolean;
+  /** resolves when the process exits */
+  exitPromise: Promise<void>;
+  /** set when the process exited after changing paths denied by the write policy (now reverted) */
+  writePolicyViolation?: string;
+}
+
+function getBackgroundProcesses(ctx: ToolContext): Map<string, BackgroundProcess> {
+  ctx.toolState.backgroundProcesses ??= new Map();
+  return ctx.toolState.backgroundProcesses;
+}
+
+function getBackgroundProcess(ctx: ToolContext, handle: string): BackgroundProcess {
+  const bg = getBackgroundProcesses(ctx).get(handle);
+  if (!bg) {
+    throw new Error(`no background process with handle ${handle}`);
+  }
+  return bg;
+}
+
+/** return output not yet read and advance the read offset */
+function readNewOutput(bg: BackgroundProcess): { output: string; truncated: boolean } {
+  const start = Math.max(bg.readOffset - bg.dropped, 0);
+  const truncated = bg.readOffset < bg.dropped;
+  const output = bg.output.slice(start);
+  bg.readOffset = bg.dropped + bg.output.length;
+  return { output, truncated };
+}
+
+/** kill all background processes started during this run (called on MCP server shutdown) */
+export async function killBackgroundProcesses(ctx: ToolContext): Promise<void> {
+  const processes = ctx.toolState.backgroundProcesses;
+  if (!processes) return;
+  await Promise.all(
+    [...processes.values()].filter((bg) => !bg.exited).map((bg) => killProcessGroup(bg.proc))
+  );
+}
+
+export const BashBackgroundParams = type({
+  command: "string",
+  description: "string",
+  "working_directory?": "string",
+});
+
+export function BashBackgroundTool(ctx: ToolContext) {
+  const isPublicRepo = !ctx.repo.repo.private;
+
+  return tool({
+    name: "bash_background",
+    description: `Start a long-running shell command (dev server, watcher, etc.) in the background and return immediately with a handle.${isPublicRepo ? " Environment is filtered to remove API keys and secrets." : ""}
+
+Use bash_read_output with the handle to poll its output and bash_kill to stop it. Background processes are killed automatically when the run ends. Changes to paths denied by the repository write policy are reverted when the process exits.`,
+    parameters: BashBackgroundParams,
+    execute: execute(async (params) => {
+      const processes = getBackgroundProcesses(ctx);
+      const handle = `bg-${processes.size + 1}`;
+      const before = snapshotForWritePolicy(ctx);
+      const proc = spawnSandboxed(params.command, {
+        env: filterEnv(isPublicRepo),
+        cwd: params.working_directory ?? process.cwd(),
+        ...(await resolveSandboxOptions(ctx)),
       });
 
-      const timeoutId = setTimeout(async () => {
-        if (!exited) {
-          timedOut = true;
-          await killProcessGroup(proc);
+      const bg: BackgroundProcess = {
+        handle,
+        command: params.command,
+        proc,
+        output: "",
+        dropped: 0,
+        readOffset: 0,
+        exitCode: null,
+        exited: false,
+        exitPromise: Promise.resolve(),
+      };
+
+      const append = (chunk: Buffer) => {
+        bg.output += chunk.toString();
+        const overflow = bg.output.length - MAX_BACKGROUND_BUFFER_CHARS;
+        if (overflow > 0) {
+          bg.output = bg.output.slice(overflow);
+          bg.dropped += overflow;
         }
-      }, timeout);
+      };
+      proc.stdout?.on("data", append);
+      proc.stderr?.on("data", append);
 
-      const exitCode = await new Promise<number | null>((resolve) => {
+      bg.exitPromise = new Promise<void>((resolve) => {
         const done = (code: number | null) => {
-          exited = true;
-          clearTimeout(timeoutId);
-          resolve(code);
+          if (bg.exited) return;
+          bg.exited = true;
+          bg.exitCode = code ?? -1;
+          // the process can write denied paths at any point, so check once it has exited
+          if (before) {
+            try {
+              enforceWritePolicy(
+                ctx,
+                before,
+                `Background process ${handle} exited with code ${bg.exitCode}`
+              );
+            } catch (error) {
+              bg.writePolicyViolation = error instanceof Error ? error.message : String(error);
+              log.warning(`» ${bg.writePolicyViolation}`);
+            }
+          }
+          resolve();
         };
         proc.on("exit", done);
         proc.on("error", () => done(null));
       });
 
-      let output = stderr ? (stdout ? `${stdout}\n${stderr}` : stderr) : stdout;
-      if (timedOut)
-        output = output
-          ? `${output}\n[timed out after ${timeout}ms]`
-          : `[timed out after ${timeout}ms]`;
+      processes.set(handle, bg);
+
+      return {
+        handle,
+        pid: proc.pid ?? null,
+        message: `Started in background. Use bash_read_output with handle "${handle}" to read its output.`,
+      };
+    }),
+  });
+}
+
+export const BashReadOutputParams = type({
+  handle: type.string.describe("Handle returned by bash_background"),
+  wait_ms: type.number
+    .describe(
+      `If there is no new output yet, wait up to this many milliseconds for output or exit (max ${MAX_READ_WAIT_MS})`
+    )
+    .default(0),
+  head_lines: type.number
+    .describe(`Lines to keep from the start of long output (default ${DEFAULT_HEAD_LINES})`)
+    .default(DEFAULT_HEAD_LINES),
+  tail_lines: type.number
+    .describe(`Lines to keep from the end of long output (default ${DEFAULT_TAIL_LINES})`)
+    .default(DEFAULT_TAIL_LINES),
+});
+
+export function BashReadOutputTool(ctx: ToolContext) {
+  return tool({
+    name: "bash_read_output",
+    description:
+      "Read new output from a background process started with bash_background. Each call returns only output produced since the previous call, plus whether the process is still running.",
+    parameters: BashReadOutputParams,
+    execute: execute(async (params) => {
+      const bg = getBackgroundProcess(ctx, params.handle);
+      const waitMs = Math.min(params.wait_ms, MAX_READ_WAIT_MS);
 
+      const hasNewOutput = () => bg.dropped + bg.output.length > bg.readOffset;
+      const deadline = Date.now() + waitMs;
+      while (!bg.exited && !hasNewOutput() && Date.now() < deadline) {
+        await Promise.race([
+          bg.exitPromise,
+          new Promise((r) => setTimeout(r, Math.min(250, deadline - Date.now()))),
+        ]);
+      }
+
+      const { output, truncated } = readNewOutput(bg);
+      const limited = limitOutput(truncated ? `[earlier output dropped]\n${output}` : output, {
+        headLines: params.head_lines,
+        tailLines: params.tail_lines,
+      });
+      return {
+        handle: bg.handle,
+        running: !bg.exited,
+        exit_code: bg.exitCode,
+        ...limited,
+        ...(bg.writePolicyViolation ? { write_policy_violation: bg.writePolicyViolation } : {}),
+      };
+    }),
+  });
+}
+
+export const BashKillParams = type({
+  handle: type.string.describe("Handle returned by bash_background"),
+});
+
+export function BashKillTool(ctx: ToolContext) {
+  return tool({
+    name: "bash_kill",
+    description:
+      "Stop a background process started with bash_background (and all of its child processes). Returns any output not yet read.",
+    parameters: BashKillParams,
+    execute: execute(async (params) => {
+      const bg = getBackgroundProcess(ctx, params.handle);
+      if (!bg.exited) {
+        await killProcessGroup(bg.proc);
+        await bg.exitPromise;
+      }
+
+      const { output, truncated } = readNewOutput(bg);
+      const limited = limitOutput(truncated ? `[earlier output dropped]\n${output}` : output, {
+        headLines: DEFAULT_HEAD_LINES,
+        tailLines: DEFAULT_TAIL_LINES,
+      });
       return {
-        output: output.trim(),
-        exit_code: exitCode ?? (timedOut ? 124 : -1),
-        timed_out: timedOut,
+        handle: bg.handle,
+        exit_code: bg.exitCode,
+        ...limited,
+        ...(bg.writePolicyViolation ? { write_policy_violation: bg.writePolicyViolation } : {}),
       };
     }),
   });
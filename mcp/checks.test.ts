@@ -0,0 +1,51 @@
+import { mkdtempSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { discoverChecks } from './checks.ts';
+
+describe('discoverChecks', () => {
+  let repo: string;
+
+  beforeEach(() => {
+    repo = mkdtempSync(join(tmpdir(), 'pullfrog-checks-test-'));
+  });
+
+  afterEach(() => {
+    rmSync(repo, { recursive: true, force: true });
+  });
+
+  it('finds package.json scripts using the detected package manager', async () => {
+    writeFileSync(
+      join(repo, 'package.json'),
+      JSON.stringify({ scripts: { build: 'tsc', lint: 'eslint .', test: 'vitest run', typecheck: 'tsc --noEmit' } })
+    );
+    writeFileSync(join(repo, 'pnpm-lock.yaml'), '');
+    const checks = await discoverChecks(repo, undefined);
+    expect(checks.map((c) => [c.name, c.kind, c.run])).toEqual([
+      ['pnpm:typecheck', 'typecheck', 'pnpm run typecheck'],
+      ['pnpm:lint', 'lint', 'pnpm run lint'],
+      ['pnpm:test', 'test', 'pnpm run test'],
+    ]);
+  });
+
+  it('prefers a Makefile test target over language defaults', async () => {
+    writeFileSync(join(repo, 'Cargo.toml'), '[package]\nname = "x"\n');
+    writeFileSync(join(repo, 'Makefile'), 'VERSION := 1\n\ntest:\n\tcargo test --all\n');
+    const checks = await discoverChecks(repo, undefined);
+    expect(checks.map((c) => c.name)).toEqual(['make:test']);
+  });
+
+  it('detects cargo, go and pytest projects', async () => {
+    writeFileSync(join(repo, 'Cargo.toml'), '[package]\nname = "x"\n');
+    writeFileSync(join(repo, 'go.mod'), 'module x\n');
+    writeFileSync(join(repo, 'pyproject.toml'), '[tool.pytest.ini_options]\naddopts = "-q"\n');
+    const checks = await discoverChecks(repo, undefined);
+    expect(checks.map((c) => c.run)).toEqual(['cargo test', 'go test ./...', 'python -m pytest']);
+  });
+
+  it('uses configured checks instead of discovery', async () => {
+    writeFileSync(join(repo, 'Cargo.toml'), '[package]\nname = "x"\n');
+    const configured = [{ name: 'unit', kind: 'test' as const, run: 'just test' }];
+    expect(await discoverChecks(repo, configured)).toEqual(configured);
+  });
+});
@@ -0,0 +1,205 @@
+import { existsSync, readFileSync } from "node:fs";
+import { join } from "node:path";
+import { type } from "arktype";
+import { detect } from "package-manager-detector";
+import { resolveCommand } from "package-manager-detector/commands";
+import { redactSecrets } from "../utils/secrets.ts";
+import {
+  enforceWritePolicy,
+  resolveSandboxOptions,
+  runSandboxedCommand,
+  snapshotForWritePolicy,
+} from "./bash.ts";
+import type { ToolContext } from "./server.ts";
+import { execute, tool } from "./shared.ts";
+
+const DEFAULT_CHECK_TIMEOUT_MS = 10 * 60 * 1000;
+const MAX_CHECK_OUTPUT_CHARS = 4000;
+
+export type CheckKind = "test" | "lint" | "typecheck";
+
+export interface Check {
+  name: string;
+  kind: CheckKind;
+  run: string;
+  /** repo-relative working directory */
+  cwd?: string | undefined;
+  timeout?: number | undefined;
+}
+
+// arktype schema for Check validation
+export const CheckSchema = type({
+  name: "string > 0",
+  kind: "'test' | 'lint' | 'typecheck'",
+  run: "string > 0",
+  "cwd?": "string",
+  "timeout?": "number > 0",
+});
+
+// script/target names that map to a check kind, in the order they are reported
+const CHECK_NAMES: Record<string, CheckKind> = {
+  typecheck: "typecheck",
+  "type-check": "typecheck",
+  "check-types": "typecheck",
+  tsc: "typecheck",
+  lint: "lint",
+  test: "test",
+};
+
+async function discoverPackageScripts(cwd: string): Promise<Check[]> {
+  const packageJsonPath = join(cwd, "package.json");
+  if (!existsSync(packageJsonPath)) return [];
+  const scripts: Record<string, string> =
+    JSON.parse(readFileSync(packageJsonPath, "utf-8")).scripts ?? {};
+
+  const agent = (await detect({ cwd }))?.agent ?? "npm";
+  const checks: Check[] = [];
+  for (const [script, kind] of Object.entries(CHECK_NAMES)) {
+    if (!scripts[script]) continue;
+    const command = resolveCommand(agent, "run", [script]);
+    if (!command) continue;
+    checks.push({
+      name: `${agent}:${script}`,
+      kind,
+      run: [command.command, ...command.args].join(" "),
+    });
+  }
+  return checks;
+}
+
+function discoverMakeTargets(cwd: string): Check[] {
+  const makefilePath = join(cwd, "Makefile");
+  if (!existsSync(makefilePath)) return [];
+  const content = readFileSync(makefilePath, "utf-8");
+  const checks: Check[] = [];
+  for (const [target, kind] of Object.entries(CHECK_NAMES)) {
+    const escaped = target.replace(/[.*+?^${}()|[\]\\]/g, "\\$&");
+    if (new RegExp(`^${escaped}\\s*:(?!=)`, "m").test(content)) {
+      checks.push({ name: `make:${target}`, kind, run: `make ${target}` });
+    }
+  }
+  return checks;
+}
+
+function usesPytest(cwd: string): boolean {
+  if (["pytest.ini", "conftest.py"].some((file) => existsSync(join(cwd, file)))) return true;
+  for (const file of ["pyproject.toml", "setup.cfg", "tox.ini"]) {
+    const path = join(cwd, file);
+    if (existsSync(path) && /^\[(tool[.:])?pytest/m.test(readFileSync(path, "utf-8"))) {
+      return true;
+    }
+  }
+  return false;
+}
+
+/**
+ * the repo's verification commands: `checks` from repo settings, or discovered from the checkout
+ */
+export async function discoverChecks(cwd: string, configured: Check[] | undefined): Promise<Check[]> {
+  if (configured) return configured;
+
+  const checks = [...(await discoverPackageScripts(cwd)), ...discoverMakeTargets(cwd)];
+  // a Makefile test target usually wraps the language defaults
+  const hasMakeTest = checks.some(
+    (check) => check.name.startsWith("make:") && check.kind === "test"
+  );
+
+  if (!hasMakeTest) {
+    if (existsSync(join(cwd, "Cargo.toml"))) {
+      checks.push({ name: "cargo:test", kind: "test", run: "cargo test" });
+    }
+    if (existsSync(join(cwd, "go.mod")) || existsSync(join(cwd, "go.work"))) {
+      checks.push({ name: "go:test", kind: "test", run: "go test ./..." });
+    }
+    if (usesPytest(cwd)) {
+      checks.push({ name: "pytest", kind: "test", run: "python -m pytest" });
+    }
+  }
+
+  return checks;
+}
+
+/**
+ * keep the end of the output, where test runners and compilers put failures and summaries
+ */
+function truncateOutput(output: string): string {
+  if (output.length <= MAX_CHECK_OUTPUT_CHARS) return output;
+  const omitted = output.length - MAX_CHECK_OUTPUT_CHARS;
+  return `[${omitted} earlier characters truncated]\n${output.slice(-MAX_CHECK_OUTPUT_CHARS)}`;
+}
+
+export const RunChecksParams = type({
+  names: type.string
+    .array()
+    .describe(
+      "Only run checks with these names (from a previous run_checks call). Defaults to all."
+    )
+    .optional(),
+  kinds: type
+    .enumerated("test", "lint", "typecheck")
+    .array()
+    .describe("Only run checks of these kinds. Defaults to all.")
+    .optional(),
+  list_only: type.boolean
+    .describe("Return the discovered checks without running them")
+    .default(false),
+});
+
+export function RunChecksTool(ctx: ToolContext) {
+  return tool({
+    name: "run_checks",
+    description: `Discover and run the repository's test, lint and typecheck commands (package.json scripts, Makefile targets, cargo test, go test, pytest, or \`checks\` in the repo settings). Returns pass/fail per command with truncated output. Use this to verify changes instead of guessing test commands. Await dependency installation first.`,
+    parameters: RunChecksParams,
+    execute: execute(async (params) => {
+      const cwd = process.cwd();
+      const checks = (await discoverChecks(cwd, ctx.repo.repoSettings.checks)).filter(
+        (check) =>
+          (!params.names || params.names.includes(check.name)) &&
+          (!params.kinds || params.kinds.includes(check.kind))
+      );
+
+      if (checks.length === 0) {
+        return {
+          passed: false,
+          message:
+            "No matching checks found. Inspect the repository to find its test commands and run them with bash.",
+          checks: [],
+        };
+      }
+
+      if (params.list_only) {
+        return { checks: checks.map(({ name, kind, run }) => ({ name, kind, command: run })) };
+      }
+
+      const sandbox = await resolveSandboxOptions(ctx);
+      const before = snapshotForWritePolicy(ctx);
+      const results = [];
+      for (const check of checks) {
+        const result = await runSandboxedCommand(check.run, {
+          cwd: check.cwd ? join(cwd, check.cwd) : cwd,
+          timeout: check.timeout ?? DEFAULT_CHECK_TIMEOUT_MS,
+          ...sandbox,
+        });
+        results.push({
+          name: check.name,
+          kind: check.kind,
+          command: check.run,
+          passed: result.exitCode === 0,
+          exit_code: result.exitCode,
+          timed_out: result.timedOut,
+          output: truncateOutput(redactSecrets(result.output)),
+        });
+      }
+
+      const passed = results.every((result) => result.passed);
+      if (before) {
+        enforceWritePolicy(ctx, before, `Checks finished (${passed ? "passed" : "failed"})`);
+      }
+
+      return {
+        passed,
+        checks: results,
+      };
+    }),
+  });
+}
@@ -0,0 +1,81 @@
+import type { ToolResult } from "./shared.ts";
+
+/**
+ * Minimal MCP client for the streamable HTTP transport: JSON-RPC over POST, replies as JSON or SSE.
+ * Used by the scripted agent and the MCP test harness to call tools on the gh_pullfrog server.
+ */
+export class McpClient {
+  readonly url: string;
+  private readonly clientName: string;
+  private sessionId: string | undefined;
+  private nextId = 1;
+
+  constructor(url: string, clientName = "pullfrog") {
+    this.url = url;
+    this.clientName = clientName;
+  }
+
+  async initialize(): Promise<void> {
+    await this.request("initialize", {
+      protocolVersion: "2025-03-26",
+      capabilities: {},
+      clientInfo: { name: this.clientName, version: "0.0.0" },
+    });
+    await this.post({ jsonrpc: "2.0", method: "notifications/initialized" });
+  }
+
+  async listTools(): Promise<string[]> {
+    const result = (await this.request("tools/list", {})) as { tools: { name: string }[] };
+    return result.tools.map((tool) => tool.name);
+  }
+
+  async callTool(name: string, args: Record<string, unknown> = {}): Promise<ToolResult> {
+    return (await this.request("tools/call", { name, arguments: args })) as ToolResult;
+  }
+
+  async close(): Promise<void> {
+    if (!this.sessionId) return;
+    await fetch(this.url, { method: "DELETE", headers: { "mcp-session-id": this.sessionId } }).catch(
+      () => {}
+    );
+  }
+
+  private async post(message: Record<string, unknown>): Promise<Response> {
+    const response = await fetch(this.url, {
+      method: "POST",
+      headers: {
+        "content-type": "application/json",
+        accept: "application/json, text/event-stream",
+        ...(this.sessionId && { "mcp-session-id": this.sessionId }),
+      },
+      body: JSON.stringify(message),
+    });
+    this.sessionId ??= response.headers.get("mcp-session-id") ?? undefined;
+    return response;
+  }
+
+  private async request(method: string, params: Record<string, unknown>): Promise<unknown> {
+    const id = this.nextId++;
+    const response = await this.post({ jsonrpc: "2.0", id, method, params });
+    const text = await response.text();
+    if (!response.ok) {
+      throw new Error(`MCP ${method} failed with ${response.status}: ${text}`);
+    }
+    // SSE replies may carry notifications before the response
+    const messages = response.headers.get("content-type")?.includes("text/event-stream")
+      ? text
+          .split("\n")
+          .filter((line) => line.startsWith("data:"))
+          .map((line) => JSON.parse(line.slice("data:".length)))
+      : [JSON.parse(text)];
+    const reply = messages.find((message) => message.id === id);
+    if (!reply) throw new Error(`MCP ${method} got no response`);
+    if (reply.error) throw new Error(`MCP ${method} failed: ${reply.error.message}`);
+    return reply.result;
+  }
+}
+
+/** text of a tool result, e.g. the TOON-encoded success data or "Error: ..." */
+export function toolResultText(result: ToolResult): string {
+  return result.content.map((part) => part.text).join("\n");
+}
@@ -2,10 +2,12 @@ import { type } from "arktype";
 import type { Agent } from "../agents/index.ts";
 import { buildPullfrogFooter, stripExistingFooter } from "../utils/buildPullfrogFooter.ts";
 import { createOctokit, type OctokitWithPlugins, parseRepoContext } from "../utils/github.ts";
+import { gitHubWebUrl } from "../utils/githubHost.ts";
+import { getPullfrogApiUrl } from "../utils/selfHosted.ts";
 import { getGitHubInstallationToken } from "../utils/token.ts";
 import { fetchWorkflowRunInfo } from "../utils/workflowRun.ts";
 import type { ToolContext, ToolState } from "./server.ts";
-import { execute, tool } from "./shared.ts";
+import { execute, filterOutboundContent, tool } from "./shared.ts";
 
 /**
  * The prefix text for the initial "leaping into action" comment.
@@ -69,13 +71,15 @@ async function buildCommentFooter({
 const SUGGESTION_FORMAT_DESCRIPTION =
   "when suggesting code changes, use GitHub's suggestion format with ```suggestion blocks to enable one-click apply (e.g., 'you could do this\\n```suggestion\\nsuggested code here\\n```'). note: suggestions only work on pull request line-level review comments, not on issue/PR-level comments.";
 
+// null in self-hosted mode, where there is no Pullfrog API to trigger the implementation
 function buildImplementPlanLink(
   owner: string,
   repo: string,
   issueNumber: number,
   commentId: number
-): string {
-  const apiUrl = process.env.API_URL || "https://pullfrog.com";
+): string | null {
+  const apiUrl = getPullfrogApiUrl();
+  if (!apiUrl) return null;
   return `[Implement plan ➔](${apiUrl}/trigger/${owner}/${repo}/${issueNumber}?action=implement&comment_id=${commentId})`;
 }
 
@@ -102,7 +106,8 @@ export function CreateCommentTool(ctx: ToolContext) {
       "Create a comment on a GitHub issue. NOTE: Do NOT use this for progress updates or status summaries - use report_progress instead, which updates the existing progress comment.",
     parameters: Comment,
     execute: execute(async ({ issueNumber, body }) => {
-      const bodyWithFooter = await addFooter(ctx, body);
+      const safeBody = filterOutboundContent(ctx, "create_issue_comment", body);
+      const bodyWithFooter = await addFooter(ctx, safeBody);
 
       const result = await ctx.octokit.rest.issues.createComment({
         owner: ctx.repo.owner,
@@ -132,7 +137,8 @@ export function EditCommentTool(ctx: ToolContext) {
     description: "Edit a GitHub issue comment by its ID",
     parameters: EditComment,
     execute: execute(async ({ commentId, body }) => {
-      const bodyWithFooter = await addFooter(ctx, body);
+      const safeBody = filterOutboundContent(ctx, "edit_issue_comment", body);
+      const bodyWithFooter = await addFooter(ctx, safeBody);
 
       const result = await ctx.octokit.rest.issues.updateComment({
         owner: ctx.repo.owner,
@@ -161,16 +167,18 @@ export const ReportProgress = type({
  * Can be called directly without going through the MCP tool interface.
  * Returns result data if successful.
  * When there's no comment target (no progressCommentId and no issueNumber), returns a "skipped" result.
+ * The body goes through filterOutboundContent, so every caller posts filtered content.
  */
 export async function reportProgress(
   ctx: ToolContext,
-  { body }: { body: string }
+  { body: unfilteredBody }: { body: string }
 ): Promise<{
   commentId?: number;
   url?: string;
   body: string;
   action: "created" | "updated" | "skipped";
 }> {
+  const body = filterOutboundContent(ctx, "report_progress", unfilteredBody);
   // always track the body for job summary
   ctx.toolState.lastProgressBody = body;
 
@@ -181,10 +189,11 @@ export async function reportProgress(
 
   // if we already have a progress comment, update it
   if (existingCommentId) {
-    const customParts =
+    const planLink =
       isPlanMode && issueNumber !== undefined
-        ? [buildImplementPlanLink(ctx.repo.owner, ctx.repo.name, issueNumber, existingCommentId)]
-        : undefined;
+        ? buildImplementPlanLink(ctx.repo.owner, ctx.repo.name, issueNumber, existingCommentId)
+        : null;
+    const customParts = planLink ? [planLink] : undefined;
 
     const bodyWithoutFooter = stripExistingFooter(body);
     const footer = await buildCommentFooter({
@@ -236,10 +245,11 @@ export async function reportProgress(
   };
 
   // if Plan mode, update the comment to add the "Implement plan" link
-  if (isPlanMode) {
-    const customParts = [
-      buildImplementPlanLink(ctx.repo.owner, ctx.repo.name, issueNumber, result.data.id),
-    ];
+  const planLink = isPlanMode
+    ? buildImplementPlanLink(ctx.repo.owner, ctx.repo.name, issueNumber, result.data.id)
+    : null;
+  if (planLink) {
+    const customParts = [planLink];
     const bodyWithoutFooter = stripExistingFooter(body);
     const footer = await buildCommentFooter({
       agent: ctx.agent,
@@ -271,6 +281,16 @@ export async function reportProgress(
   };
 }
 
+/**
+ * Final progress update for a run stopped by its budget.
+ * Keeps whatever the agent last reported and explains the cutoff below it.
+ */
+export async function reportBudgetCutoff(ctx: ToolContext, reason: string): Promise<void> {
+  const notice = `⚠️ This run was stopped early because ${reason}. The work above may be incomplete.`;
+  const previous = ctx.toolState.lastProgressBody;
+  await reportProgress(ctx, { body: previous ? `${previous}\n\n---\n\n${notice}` : notice });
+}
+
 export function ReportProgressTool(ctx: ToolContext) {
   return tool({
     name: "report_progress",
@@ -399,7 +419,7 @@ export async function ensureProgressCommentUpdated(toolState: ToolState): Promis
 
   const runId = process.env.GITHUB_RUN_ID;
   const workflowRunLink = runId
-    ? `[workflow run logs](https://github.com/${repoContext.owner}/${repoContext.name}/actions/runs/${runId})`
+    ? `[workflow run logs](${gitHubWebUrl(`${repoContext.owner}/${repoContext.name}/actions/runs/${runId}`)})`
     : "workflow run logs";
 
   const errorMessage = `This run croaked 😵
@@ -407,7 +427,10 @@ export async function ensureProgressCommentUpdated(toolState: ToolState): Promis
 The workflow encountered an error before any progress could be reported. Please check the ${workflowRunLink} for details.`;
 
   // add footer without agent info (we don't have context here)
-  const body = await addFooter({ octokit }, errorMessage);
+  const body = await addFooter(
+    { octokit },
+    filterOutboundContent({ toolState }, "progress comment", errorMessage)
+  );
 
   await octokit.rest.issues.updateComment({
     owner: repoContext.owner,
@@ -432,7 +455,8 @@ export function ReplyToReviewCommentTool(ctx: ToolContext) {
       "Reply to a PR review comment thread. Call this for EACH comment you address. Keep replies extremely brief (1 sentence max).",
     parameters: ReplyToReviewComment,
     execute: execute(async ({ pull_number, comment_id, body }) => {
-      const bodyWithFooter = await addFooter(ctx, body);
+      const safeBody = filterOutboundContent(ctx, "reply_to_review_comment", body);
+      const bodyWithFooter = await addFooter(ctx, safeBody);
 
       const result = await ctx.octokit.rest.pulls.createReplyForReviewComment({
         owner: ctx.repo.owner,
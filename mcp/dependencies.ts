@@ -1,22 +1,58 @@
 import { type } from "arktype";
-import type { ToolContext } from "./server.ts";
 import type { PrepResult } from "../prep/index.ts";
 import { runPrepPhase } from "../prep/index.ts";
+import { $ } from "../utils/shell.ts";
+import { usesNetworkSandbox } from "./bash.ts";
+import type { ToolContext } from "./server.ts";
 import { execute, tool } from "./shared.ts";
 
-// empty schema for tools with no parameters
-const EmptyParams = type({});
+export const DependencyInstallationParams = type({
+  scope: type
+    .enumerated("all", "changed")
+    .describe(
+      "'all' (default) installs every project in the repo. 'changed' only installs nested projects touched by the current PR (the repo root is always installed)."
+    )
+    .optional(),
+});
 
-/**
- * format prep results into agent-friendly message
- */
-function formatPrepResults(results: PrepResult[]): string {
-  if (results.length === 0) {
-    return `No supported language detected in this repository (checked for package.json, requirements.txt, pyproject.toml, etc.).
+const NO_LANGUAGE_DETECTED = `No supported language detected in this repository (checked for package.json, requirements.txt, pyproject.toml, Cargo.toml, go.mod, etc.).
 
 Inspect the repository structure to determine how dependencies should be installed, then use bash to install them.`;
+
+type LanguagePrepResult = Exclude<PrepResult, { language: "unknown" }>;
+
+/**
+ * describe a prep result as "<language>" and "<installer (source)>" for display
+ */
+function describePrepResult(result: LanguagePrepResult): { language: string; via: string } {
+  switch (result.language) {
+    case "node":
+      return { language: "Node.js", via: result.packageManager };
+    case "python":
+      return { language: "Python", via: `${result.packageManager} (from ${result.configFile})` };
+    case "rust":
+      return {
+        language: "Rust",
+        via: result.toolchain ? `cargo (toolchain ${result.toolchain})` : "cargo",
+      };
+    case "script":
+      return { language: `Setup step "${result.name}"`, via: `\`${result.command}\`` };
+    case "go":
+      return {
+        language: "Go",
+        via: `go mod download (from ${result.configFile}${result.toolchain ? `, toolchain ${result.toolchain}` : ""})`,
+      };
+    default: {
+      const _exhaustive: never = result;
+      return _exhaustive satisfies never;
+    }
   }
+}
 
+/**
+ * format prep results into agent-friendly message
+ */
+function formatPrepResults(results: PrepResult[]): string {
   const lines: string[] = [];
 
   for (const result of results) {
@@ -24,59 +60,139 @@ Inspect the repository structure to determine how dependencies should be install
       continue;
     }
 
-    const langDisplay = result.language === "node" ? "Node.js" : "Python";
+    const described = describePrepResult(result);
+    // nested monorepo projects are reported with their path
+    const language =
+      result.path && result.path !== "."
+        ? `${described.language} (\`${result.path}\`)`
+        : described.language;
+    const via = described.via;
+
+    if (result.language === "script") {
+      if (result.dependenciesInstalled) {
+        lines.push(`${language} completed successfully (${via}).`);
+      } else {
+        lines.push(`${language} ${result.skipped ? "was skipped" : "failed"}${result.command ? ` (${via})` : ""}.
+
+Error:
+${result.issues.join("\n") || "unknown error"}
+
+Use bash or other tools at your disposal to diagnose and resolve the issue, then perform this setup manually if your task needs it.`);
+      }
+      continue;
+    }
 
     if (result.dependenciesInstalled) {
-      if (result.language === "node") {
-        lines.push(
-          `${langDisplay} dependencies installed successfully via ${result.packageManager}.`
-        );
-      } else if (result.language === "python") {
-        lines.push(
-          `${langDisplay} dependencies installed successfully via ${result.packageManager} (from ${result.configFile}).`
-        );
+      let line = `${language} dependencies installed successfully via ${via}.`;
+      // installed results can still carry issues (e.g. a failed rust pre-build)
+      if (result.language === "rust" && result.prebuilt) {
+        line += " Tests were pre-built with `cargo build --tests`.";
+      } else if (result.issues.length > 0) {
+        line += `\n\nWarnings:\n${result.issues.join("\n")}`;
       }
+      lines.push(line);
     } else {
       const errorMsg = result.issues.length > 0 ? result.issues.join("\n") : "unknown error";
-
-      if (result.language === "node") {
-        lines.push(`${langDisplay} dependency installation failed via ${result.packageManager}.
+      lines.push(`${language} dependency installation failed via ${via}.
 
 Error:
 ${errorMsg}
 
 Use bash or other tools at your disposal to diagnose and resolve the issue, then install dependencies manually.`);
-      } else if (result.language === "python") {
-        lines.push(`${langDisplay} dependency installation failed via ${result.packageManager} (from ${result.configFile}).
-
-Error:
-${errorMsg}
-
-Use bash or other tools at your disposal to diagnose and resolve the issue, then install dependencies manually.`);
-      }
     }
   }
 
   if (lines.length === 0) {
-    return `No supported language detected in this repository (checked for package.json, requirements.txt, pyproject.toml, etc.).
-
-Inspect the repository structure to determine how dependencies should be installed, then use bash to install them.`;
+    return NO_LANGUAGE_DETECTED;
   }
 
   return lines.join("\n\n");
 }
 
+/**
+ * files changed by the current PR, or undefined when there is no PR context
+ */
+async function getChangedFiles(ctx: ToolContext): Promise<string[] | undefined> {
+  const pull_number = ctx.toolState.prNumber;
+  if (!pull_number) return undefined;
+  const files = await ctx.octokit.paginate(ctx.octokit.rest.pulls.listFiles, {
+    owner: ctx.repo.owner,
+    repo: ctx.repo.name,
+    pull_number,
+  });
+  // renames touch both the old and the new location
+  return files.flatMap((file) =>
+    file.previous_filename ? [file.filename, file.previous_filename] : [file.filename]
+  );
+}
+
+/**
+ * HEAD at startup if it's the default branch as on GitHub, or null (e.g. a PR checkout)
+ */
+export async function resolveDefaultBranchHead(ctx: ToolContext): Promise<string | null> {
+  try {
+    const head = $("git", ["rev-parse", "HEAD"], { log: false });
+    const branch = await ctx.octokit.rest.repos.getBranch({
+      owner: ctx.repo.owner,
+      repo: ctx.repo.name,
+      branch: ctx.repo.repo.default_branch || "main",
+    });
+    return branch.data.commit.sha === head ? head : null;
+  } catch {
+    return null;
+  }
+}
+
+/**
+ * prep runs on the host with full network access: package manager installs run lifecycle scripts
+ * and setup steps run arbitrary commands. with the isolated sandbox, that would let a PR or the
+ * agent send data out, so prep only runs on the unchanged default branch checked out at startup.
+ */
+function assertDefaultBranchCheckout(ctx: ToolContext): void {
+  if (!usesNetworkSandbox(ctx.payload)) return;
+  const refusal =
+    "Dependency installation runs outside the isolated sandbox, so it only runs on the default " +
+    "branch as checked out at startup.";
+  const base = ctx.toolState.defaultBranchHead;
+  if (!base) {
+    throw new Error(`${refusal} This run didn't start on it. Install dependencies with bash instead.`);
+  }
+  if ($("git", ["rev-parse", "HEAD"], { log: false }) !== base) {
+    throw new Error(
+      `${refusal} A different commit is checked out now (e.g. by checkout_pr). Install dependencies with bash instead.`
+    );
+  }
+  const changed = [
+    ...$("git", ["diff", "--name-only", base], { log: false }).split("\n"),
+    ...$("git", ["ls-files", "--others", "--exclude-standard"], { log: false }).split("\n"),
+  ].filter(Boolean);
+  if (changed.length > 0) {
+    throw new Error(
+      `${refusal} These files changed during this run:\n${[...new Set(changed)].join("\n")}\n\n` +
+        "Install dependencies with bash instead."
+    );
+  }
+}
+
 /**
  * start dependency installation in the background (non-blocking, idempotent)
  */
-function startInstallation(ctx: ToolContext): void {
+function startInstallation(ctx: ToolContext, scope: "all" | "changed" = "all"): void {
   // already started or completed - do nothing
   if (ctx.toolState.dependencyInstallation) {
     return;
   }
+  assertDefaultBranchCheckout(ctx);
 
   // initialize state and start installation
-  const promise = runPrepPhase();
+  const promise = (scope === "changed" ? getChangedFiles(ctx) : Promise.resolve(undefined)).then(
+    (changedFiles) =>
+      runPrepPhase({
+        setupSteps: ctx.repo.repoSettings.prep,
+        changedFiles,
+        rustPrebuild: ctx.repo.repoSettings.rustPrebuild,
+      })
+  );
   ctx.toolState.dependencyInstallation = {
     status: "in_progress",
     promise,
@@ -104,9 +220,9 @@ export function StartDependencyInstallationTool(ctx: ToolContext) {
   return tool({
     name: "start_dependency_installation",
     description:
-      "Start installing project dependencies in the background. This is non-blocking and returns immediately. Call this early (right after branch checkout) if you anticipate needing to run tests, builds, or other commands that require dependencies. Idempotent - safe to call multiple times.",
-    parameters: EmptyParams,
-    execute: execute(async () => {
+      "Start installing project dependencies in the background. This is non-blocking and returns immediately. With the isolated sandbox it only runs on the default branch as checked out at startup, so call it before checking out a PR or editing anything. In monorepos every workspace/project is installed; pass scope 'changed' to limit installation to projects touched by the current PR. Call this early (right after branch checkout) if you anticipate needing to run tests, builds, or other commands that require dependencies. Idempotent - safe to call multiple times.",
+    parameters: DependencyInstallationParams,
+    execute: execute(async ({ scope }) => {
       const state = ctx.toolState.dependencyInstallation;
 
       // already completed
@@ -128,7 +244,7 @@ export function StartDependencyInstallationTool(ctx: ToolContext) {
       }
 
       // start installation
-      startInstallation(ctx);
+      startInstallation(ctx, scope);
 
       return {
         status: "started",
@@ -144,11 +260,11 @@ export function AwaitDependencyInstallationTool(ctx: ToolContext) {
     name: "await_dependency_installation",
     description:
       "Wait for dependency installation to complete and get the results. If installation hasn't been started yet, this will start it automatically. Call this before running tests, builds, or other commands that require dependencies.",
-    parameters: EmptyParams,
-    execute: execute(async () => {
+    parameters: DependencyInstallationParams,
+    execute: execute(async ({ scope }) => {
       // auto-start if not started
       if (!ctx.toolState.dependencyInstallation) {
-        startInstallation(ctx);
+        startInstallation(ctx, scope);
       }
 
       const state = ctx.toolState.dependencyInstallation;
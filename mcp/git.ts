@@ -2,8 +2,78 @@ import { type } from "arktype";
 import type { ToolContext } from "./server.ts";
 import { log } from "../utils/cli.ts";
 import { containsSecrets } from "../utils/secrets.ts";
+import {
+  formatSecretFindings,
+  scanForSecrets,
+  scanStagedChanges,
+} from "../utils/secretScanner.ts";
+import { findProtectedPaths, resolveProtectedPaths } from "../utils/protectedPaths.ts";
 import { $ } from "../utils/shell.ts";
-import { execute, tool } from "./shared.ts";
+import { assertWritable, getChangedPaths, resolveWritePolicy } from "../utils/writePolicy.ts";
+import { execute, secretsAllowlist, tool } from "./shared.ts";
+
+/**
+ * block changes to protected paths unless the triggering issue/PR carries the override label.
+ * `action` names what is blocked ("Commit", "Push", "PR creation")
+ */
+export async function assertProtectedPathsAllowed(
+  ctx: ToolContext,
+  paths: string[],
+  action: string
+): Promise<void> {
+  const settings = resolveProtectedPaths(ctx.repo.repoSettings);
+  const touched = findProtectedPaths(paths, settings.patterns);
+  if (touched.length === 0) return;
+
+  const list = touched.map((path) => `  - ${path}`).join("\n");
+  // only the triggering issue/PR counts - the agent can't pick one that happens to be labeled
+  const issueNumber = ctx.payload.event.issue_number;
+  if (settings.mode === "label" && issueNumber !== undefined) {
+    const labels = await ctx.octokit.paginate(ctx.octokit.rest.issues.listLabelsOnIssue, {
+      owner: ctx.repo.owner,
+      repo: ctx.repo.name,
+      issue_number: issueNumber,
+    });
+    if (labels.some((label) => label.name === settings.label)) {
+      log.info(`» changing protected paths approved by the "${settings.label}" label`);
+      return;
+    }
+  }
+
+  const howToAllow =
+    settings.mode === "label"
+      ? `A maintainer can allow this by adding the "${settings.label}" label to the triggering issue or PR.`
+      : "Changes to these paths must be made manually.";
+  const howToFix =
+    action === "Commit"
+      ? "Unstage them and commit the rest."
+      : "Commit a revert of these paths, then try again.";
+  throw new Error(
+    `${action} blocked: these protected paths may not be changed:\n${list}\n` +
+      `${howToFix} ${howToAllow}`
+  );
+}
+
+/**
+ * refuse staged changes that add credential files or contain secrets, including binary files
+ */
+function assertNoStagedSecrets(ctx: ToolContext): void {
+  const scan = scanStagedChanges({ allowlist: secretsAllowlist(ctx) });
+  if (scan.sensitiveFiles.length > 0) {
+    throw new Error(
+      "Commit blocked: these files usually hold credentials and must not be committed:\n" +
+        `${scan.sensitiveFiles.map((path) => `  - ${path}`).join("\n")}\n` +
+        "Unstage them and add them to .gitignore if needed."
+    );
+  }
+  if (scan.findings.length > 0 || containsSecrets(scan.text)) {
+    throw new Error(
+      "Commit blocked: secrets detected in staged changes. " +
+        "Please remove any sensitive information (API keys, tokens, passwords) before committing." +
+        (scan.findings.length > 0 ? `\n${formatSecretFindings(scan.findings)}` : "")
+    );
+  }
+}
 
 export function CreateBranchTool(ctx: ToolContext) {
   const defaultBranch = ctx.repo.repo.default_branch || "main";
@@ -34,6 +104,16 @@ export function CreateBranchTool(ctx: ToolContext) {
         );
       }
 
+      // force pushes are allowed to branches created here, so never take over an existing one
+      const existing = $("git", ["ls-remote", "--heads", "origin", `refs/heads/${branchName}`], {
+        log: false,
+      });
+      if (existing) {
+        throw new Error(
+          `Branch ${branchName} already exists on the remote. Choose a different branch name, or check it out to add commits to it.`
+        );
+      }
+
       log.debug(`Creating branch ${branchName} from ${resolvedBaseBranch}`);
 
       // fetch base branch to ensure we're up to date
@@ -48,6 +128,7 @@ export function CreateBranchTool(ctx: ToolContext) {
 
       // push branch to remote (set upstream)
       $("git", ["push", "-u", "origin", branchName]);
+      ctx.toolState.createdBranches = [...(ctx.toolState.createdBranches ?? []), branchName];
 
       log.debug(`Successfully created and pushed branch ${branchName}`);
 
@@ -70,7 +151,7 @@ export const CommitFiles = type({
     ),
 });
 
-export function CommitFilesTool(_ctx: ToolContext) {
+export function CommitFilesTool(ctx: ToolContext) {
   return tool({
     name: "commit_files",
     description:
@@ -78,36 +159,15 @@ export function CommitFilesTool(_ctx: ToolContext) {
     parameters: CommitFiles,
     execute: execute(async ({ message, files }) => {
       // validate commit message for secrets
-      if (containsSecrets(message)) {
+      const messageFindings = scanForSecrets(message, { allowlist: secretsAllowlist(ctx) });
+      if (containsSecrets(message) || messageFindings.length > 0) {
         throw new Error(
           "Commit blocked: secrets detected in commit message. " +
-            "Please remove any sensitive information (API keys, tokens, passwords) before committing."
+            "Please remove any sensitive information (API keys, tokens, passwords) before committing." +
+            (messageFindings.length > 0 ? `\n${formatSecretFindings(messageFindings)}` : "")
         );
       }
 
-      // validate files for secrets if provided
-      if (files.length > 0) {
-        for (const file of files) {
-          try {
-            // try to read file content - if it exists, check for secrets
-            const content = $("cat", [file], { log: false });
-            if (containsSecrets(content)) {
-              throw new Error(
-                `Commit blocked: secrets detected in file ${file}. ` +
-                  "Please remove any sensitive information (API keys, tokens, passwords) before committing."
-              );
-            }
-          } catch (error) {
-            // if error is about secrets, re-throw it
-            if (error instanceof Error && error.message.includes("Commit blocked")) {
-              throw error;
-            }
-            // if file doesn't exist (cat fails), that's ok - it will be created by git add
-            // other errors are also ok - git add will handle them
-          }
-        }
-      }
-
       const currentBranch = $("git", ["rev-parse", "--abbrev-ref", "HEAD"], { log: false });
       log.debug(`Committing files on branch ${currentBranch}`);
 
@@ -118,6 +178,23 @@ export function CommitFilesTool(_ctx: ToolContext) {
         $("git", ["add", "."]);
       }
 
+      // enforce the write policy and scan for secrets on what is actually staged
+      const staged = $("git", ["diff", "--cached", "--name-only", "--no-renames"], { log: false });
+      const stagedPaths = staged ? staged.split("\n") : [];
+      try {
+        assertWritable(
+          stagedPaths,
+          resolveWritePolicy(ctx.payload, ctx.repo.repoSettings),
+          "Commit"
+        );
+        await assertProtectedPathsAllowed(ctx, stagedPaths, "Commit");
+        assertNoStagedSecrets(ctx);
+      } catch (error) {
+        // leave the working tree as-is but unstage everything so a retry starts clean
+        $("git", ["reset", "-q"], { log: false });
+        throw error;
+      }
+
       // commit with message
       $("git", ["commit", "-m", message]);
 
@@ -141,7 +218,72 @@ export const PushBranch = type({
   force: type.boolean.describe("Force push (use with caution)").default(false),
 });
 
-export function PushBranchTool(_ctx: ToolContext) {
+/**
+ * refuse pushes to the default branch or branches protected on GitHub,
+ * and force pushes to branches this run didn't create
+ */
+async function assertPushAllowed(
+  ctx: ToolContext,
+  params: { remote: string; remoteBranch: string; force: boolean }
+): Promise<void> {
+  const { remote, remoteBranch, force } = params;
+
+  const createdInRun = remote === "origin" && ctx.toolState.createdBranches?.includes(remoteBranch);
+  if (force && !createdInRun) {
+    throw new Error(
+      `Push blocked: force pushing is only allowed for branches created in this run with create_branch, not ${remoteBranch}. Push without force instead.`
+    );
+  }
+
+  // fork remotes are outside this repo's default branch and protection rules
+  if (remote !== "origin") return;
+
+  const defaultBranch = ctx.repo.repo.default_branch || "main";
+  if (remoteBranch === defaultBranch) {
+    throw new Error(
+      `Push blocked: pushing directly to the default branch (${defaultBranch}) is not allowed. Create a branch with create_branch and open a pull request.`
+    );
+  }
+
+  let isProtected = false;
+  try {
+    const branch = await ctx.octokit.rest.repos.getBranch({
+      owner: ctx.repo.owner,
+      repo: ctx.repo.name,
+      branch: remoteBranch,
+    });
+    isProtected = branch.data.protected;
+  } catch (error) {
+    // a branch that doesn't exist on the remote yet can't be protected. anything else fails closed
+    if ((error as { status?: number }).status !== 404) throw error;
+  }
+  if (isProtected) {
+    throw new Error(
+      `Push blocked: ${remoteBranch} is a protected branch. Push to a new branch and open a pull request instead.`
+    );
+  }
+}
+
+/**
+ * the commit a push adds commits on top of: the remote branch when it exists and is known locally,
+ * otherwise the default branch
+ */
+function pushBase(ctx: ToolContext, remote: string, remoteBranch: string): string {
+  const remoteSha = $("git", ["ls-remote", remote, `refs/heads/${remoteBranch}`], {
+    log: false,
+  }).split(/\s/)[0];
+  if (remoteSha) {
+    try {
+      $("git", ["cat-file", "-e", `${remoteSha}^{commit}`], { log: false });
+      return remoteSha;
+    } catch {
+      // someone else pushed commits we don't have - check against the default branch
+    }
+  }
+  return `origin/${ctx.repo.repo.default_branch || "main"}`;
+}
+
+export function PushBranchTool(ctx: ToolContext) {
   return tool({
     name: "push_branch",
     description:
@@ -168,6 +310,12 @@ export function PushBranchTool(_ctx: ToolContext) {
         // no configured merge ref, use local branch name
       }
 
+      await assertPushAllowed(ctx, { remote, remoteBranch, force });
+      const changedPaths = getChangedPaths(pushBase(ctx, remote, remoteBranch), branch);
+      // commits made with bash never went through commit_files
+      assertWritable(changedPaths, resolveWritePolicy(ctx.payload, ctx.repo.repoSettings), "Push");
+      await assertProtectedPathsAllowed(ctx, changedPaths, "Push");
+
       // use refspec when local and remote branch names differ
       const refspec = branch === remoteBranch ? branch : `${branch}:${remoteBranch}`;
       const args = force
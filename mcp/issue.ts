@@ -1,6 +1,6 @@
 import { type } from "arktype";
 import type { ToolContext } from "./server.ts";
-import { execute, tool } from "./shared.ts";
+import { execute, filterOutboundContent, tool } from "./shared.ts";
 
 export const Issue = type({
   title: type.string.describe("the title of the issue"),
@@ -24,8 +24,8 @@ export function IssueTool(ctx: ToolContext) {
       const result = await ctx.octokit.rest.issues.create({
         owner: ctx.repo.owner,
         repo: ctx.repo.name,
-        title: title,
-        body: body,
+        title: filterOutboundContent(ctx, "create_issue", title),
+        body: filterOutboundContent(ctx, "create_issue", body),
         labels: labels ?? [],
         assignees: assignees ?? [],
       });
@@ -1,4 +1,5 @@
 import { type } from "arktype";
+import { resolveProtectedPaths } from "../utils/protectedPaths.ts";
 import type { ToolContext } from "./server.ts";
 import { execute, tool } from "./shared.ts";
 
@@ -14,6 +15,12 @@ export function AddLabelsTool(ctx: ToolContext) {
       "Add labels to a GitHub issue or pull request. Only use labels that already exist in the repository.",
     parameters: AddLabelsParams,
     execute: execute(async ({ issue_number, labels }) => {
+      // the override label is an approval signal from maintainers, never from the agent
+      const { label: overrideLabel } = resolveProtectedPaths(ctx.repo.repoSettings);
+      if (labels.includes(overrideLabel)) {
+        throw new Error(`the "${overrideLabel}" label can only be added by a maintainer`);
+      }
+
       const result = await ctx.octokit.rest.issues.addLabels({
         owner: ctx.repo.owner,
         repo: ctx.repo.name,
@@ -1,10 +1,13 @@
 import { type } from "arktype";
 import { buildPullfrogFooter, stripExistingFooter } from "../utils/buildPullfrogFooter.ts";
 import { log } from "../utils/cli.ts";
+import { formatSecretFindings, scanDiffForSecrets } from "../utils/secretScanner.ts";
 import { containsSecrets } from "../utils/secrets.ts";
 import { $ } from "../utils/shell.ts";
+import { assertWritable, getChangedPaths, resolveWritePolicy } from "../utils/writePolicy.ts";
+import { assertProtectedPathsAllowed } from "./git.ts";
 import type { ToolContext } from "./server.ts";
-import { execute, tool } from "./shared.ts";
+import { execute, filterOutboundContent, secretsAllowlist, tool } from "./shared.ts";
 
 export const PullRequest = type({
   title: type.string.describe("the title of the pull request"),
@@ -34,31 +37,37 @@ export function CreatePullRequestTool(ctx: ToolContext) {
       const currentBranch = $("git", ["rev-parse", "--abbrev-ref", "HEAD"], { log: false });
       log.debug(`Current branch: ${currentBranch}`);
 
-      // validate PR title and body for secrets
-      if (containsSecrets(title) || containsSecrets(body)) {
-        throw new Error(
-          "PR creation blocked: secrets detected in PR title or body. " +
-            "Please remove any sensitive information (API keys, tokens, passwords) before creating a PR."
-        );
-      }
+      // redact or reject secrets in PR title and body
+      const safeTitle = filterOutboundContent(ctx, "create_pull_request", title);
+      const safeBody = filterOutboundContent(ctx, "create_pull_request", body);
 
       // validate all changes that would be in the PR (from base to HEAD)
       // FORK PR NOTE: origin/<base> is fetched by setupGit, so this works for both fork and same-repo PRs
       // use two-dot (..) not three-dot (...) for reliable diffs with shallow clones
       const diff = $("git", ["diff", `origin/${base}..HEAD`], { log: false });
-      if (containsSecrets(diff)) {
+      const diffFindings = scanDiffForSecrets(diff, { allowlist: secretsAllowlist(ctx) });
+      if (containsSecrets(diff) || diffFindings.length > 0) {
         throw new Error(
           "PR creation blocked: secrets detected in changes. " +
-            "Please remove any sensitive information (API keys, tokens, passwords) before creating a PR."
+            "Please remove any sensitive information (API keys, tokens, passwords) before creating a PR." +
+            (diffFindings.length > 0 ? `\n${formatSecretFindings(diffFindings)}` : "")
         );
       }
 
-      const bodyWithFooter = buildPrBodyWithFooter(ctx, body);
+      const changedPaths = getChangedPaths(`origin/${base}`, "HEAD");
+      assertWritable(
+        changedPaths,
+        resolveWritePolicy(ctx.payload, ctx.repo.repoSettings),
+        "PR creation"
+      );
+      await assertProtectedPathsAllowed(ctx, changedPaths, "PR creation");
+
+      const bodyWithFooter = buildPrBodyWithFooter(ctx, safeBody);
 
       const result = await ctx.octokit.rest.pulls.create({
         owner: ctx.repo.owner,
         repo: ctx.repo.name,
-        title: title,
+        title: safeTitle,
         body: bodyWithFooter,
         head: currentBranch,
         base: base,
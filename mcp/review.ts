@@ -2,9 +2,10 @@ import type { RestEndpointMethodTypes } from "@octokit/rest";
 import { type } from "arktype";
 import { buildPullfrogFooter } from "../utils/buildPullfrogFooter.ts";
 import { log } from "../utils/cli.ts";
+import { getPullfrogApiUrl } from "../utils/selfHosted.ts";
 import { deleteProgressComment } from "./comment.ts";
 import type { ToolContext } from "./server.ts";
-import { execute, tool } from "./shared.ts";
+import { execute, filterOutboundContent, tool } from "./shared.ts";
 
 // one-shot review tool
 export const CreatePullRequestReview = type({
@@ -69,7 +70,8 @@ export function CreatePullRequestReviewTool(ctx: ToolContext) {
         pull_number,
         event: "COMMENT",
       };
-      if (body) params.body = body;
+      const safeBody = body && filterOutboundContent(ctx, "create_pull_request_review", body);
+      if (safeBody) params.body = safeBody;
       if (commit_id) {
         params.commit_id = commit_id;
       } else {
@@ -95,7 +97,7 @@ export function CreatePullRequestReviewTool(ctx: ToolContext) {
           const reviewComment: ReviewComment = {
             path: comment.path,
             line: comment.line,
-            body: commentBody,
+            body: filterOutboundContent(ctx, "create_pull_request_review", commentBody),
           };
           reviewComment.side = comment.side || "RIGHT";
           if (comment.start_line) {
@@ -114,9 +116,10 @@ export function CreatePullRequestReviewTool(ctx: ToolContext) {
 
       // build quick links footer and update the review body
       // only include "Fix all" and "Fix 👍s" links if there are actual review comments
+      // the links go through the Pullfrog API, so self-hosted runs leave them out
       const customParts: string[] = [];
-      if (comments.length > 0) {
-        const apiUrl = process.env.API_URL || "https://pullfrog.com";
+      const apiUrl = getPullfrogApiUrl();
+      if (comments.length > 0 && apiUrl) {
         const fixAllUrl = `${apiUrl}/trigger/${ctx.repo.owner}/${ctx.repo.name}/${pull_number}?action=fix&review_id=${reviewId}`;
         const fixApprovedUrl = `${apiUrl}/trigger/${ctx.repo.owner}/${ctx.repo.name}/${pull_number}?action=fix-approved&review_id=${reviewId}`;
         customParts.push(`[Fix all ➔](${fixAllUrl})`, `[Fix 👍s ➔](${fixApprovedUrl})`);
@@ -132,7 +135,7 @@ export function CreatePullRequestReviewTool(ctx: ToolContext) {
         customParts,
       });
 
-      const updatedBody = (body || "") + footer;
+      const updatedBody = (safeBody || "") + footer;
 
       // update the review with the footer
       await ctx.octokit.rest.pulls.updateReview({
@@ -337,7 +340,7 @@ export function AddReviewCommentTool(ctx: ToolContext) {
             pullRequestReviewId: reviewNodeId,
             path,
             line,
-            body,
+            body: filterOutboundContent(ctx, "add_review_comment", body),
             side: side || "RIGHT",
             subjectType: "LINE",
           }
@@ -410,17 +413,22 @@ export function SubmitReviewTool(ctx: ToolContext) {
         `submitting review: id=${reviewId}, nodeId=${ctx.toolState.review.nodeId}, prNumber=${ctx.toolState.prNumber}`
       );
 
-      // build quick links footer
-      const apiUrl = process.env.API_URL || "https://pullfrog.com";
-      const fixAllUrl = `${apiUrl}/trigger/${ctx.repo.owner}/${ctx.repo.name}/${ctx.toolState.prNumber}?action=fix&review_id=${reviewId}`;
-      const fixApprovedUrl = `${apiUrl}/trigger/${ctx.repo.owner}/${ctx.repo.name}/${ctx.toolState.prNumber}?action=fix-approved&review_id=${reviewId}`;
+      // build quick links footer (not available in self-hosted mode)
+      const customParts: string[] = [];
+      const apiUrl = getPullfrogApiUrl();
+      if (apiUrl) {
+        const fixAllUrl = `${apiUrl}/trigger/${ctx.repo.owner}/${ctx.repo.name}/${ctx.toolState.prNumber}?action=fix&review_id=${reviewId}`;
+        const fixApprovedUrl = `${apiUrl}/trigger/${ctx.repo.owner}/${ctx.repo.name}/${ctx.toolState.prNumber}?action=fix-approved&review_id=${reviewId}`;
+        customParts.push(`[Fix all ➔](${fixAllUrl})`, `[Fix 👍s ➔](${fixApprovedUrl})`);
+      }
 
       const footer = buildPullfrogFooter({
         workflowRun: { owner: ctx.repo.owner, repo: ctx.repo.name, runId: ctx.runId, jobId: ctx.jobId },
-        customParts: [`[Fix all ➔](${fixAllUrl})`, `[Fix 👍s ➔](${fixApprovedUrl})`],
+        customParts,
       });
 
-      const bodyWithFooter = (body || "") + footer;
+      const safeBody = body && filterOutboundContent(ctx, "submit_review", body);
+      const bodyWithFooter = (safeBody || "") + footer;
 
       // submit the pending review via REST
       const result = await ctx.octokit.rest.pulls.submitReview({
@@ -0,0 +1,20 @@
+import { DEFAULT_ALLOWED_HOSTS, isHostAllowed } from './sandbox.ts';
+
+describe('isHostAllowed', () => {
+  it.each([
+    ['registry.npmjs.org', true],
+    ['REGISTRY.NPMJS.ORG.', true],
+    ['evil.registry.npmjs.org', false],
+    ['registry.npmjs.org.evil.com', false],
+    ['httpbin.org', false],
+  ] as const)('default allowlist: %s -> %s', (host, expected) => {
+    expect(isHostAllowed(host, DEFAULT_ALLOWED_HOSTS)).toBe(expected);
+  });
+
+  it('matches subdomains for wildcard entries', () => {
+    const allowed = ['*.internal.example.com'];
+    expect(isHostAllowed('npm.internal.example.com', allowed)).toBe(true);
+    expect(isHostAllowed('internal.example.com', allowed)).toBe(true);
+    expect(isHostAllowed('notinternal.example.com', allowed)).toBe(false);
+  });
+});
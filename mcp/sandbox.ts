@@ -0,0 +1,232 @@
+import { execFileSync } from "node:child_process";
+import { mkdtempSync, rmSync } from "node:fs";
+import { createServer, type IncomingMessage, request, type Server } from "node:http";
+import { connect, type Socket } from "node:net";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+import { log } from "../utils/cli.ts";
+
+// package registries reachable from the isolated sandbox by default
+export const DEFAULT_ALLOWED_HOSTS = [
+  "registry.npmjs.org",
+  "registry.yarnpkg.com",
+  "pypi.org",
+  "files.pythonhosted.org",
+  "crates.io",
+  "index.crates.io",
+  "static.crates.io",
+  "proxy.golang.org",
+  "sum.golang.org",
+];
+
+// port the in-sandbox forwarder listens on (loopback of the sandbox's own network namespace)
+const SANDBOX_PROXY_PORT = 3128;
+
+/**
+ * check a hostname against the allowlist. entries starting with "*." also match subdomains.
+ */
+export function isHostAllowed(host: string, allowedHosts: string[]): boolean {
+  const normalized = host.toLowerCase().replace(/\.$/, "");
+  return allowedHosts.some((entry) => {
+    const pattern = entry.toLowerCase();
+    if (pattern.startsWith("*.")) {
+      const suffix = pattern.slice(1);
+      return normalized.endsWith(suffix) || normalized === pattern.slice(2);
+    }
+    return normalized === pattern;
+  });
+}
+
+function splitHostPort(authority: string, defaultPort: number): { host: string; port: number } {
+  const match = authority.match(/^\[?([^\]]+?)\]?(?::(\d+))?$/);
+  return { host: match?.[1] ?? authority, port: match?.[2] ? Number(match[2]) : defaultPort };
+}
+
+function isBwrapAvailable(): boolean {
+  try {
+    execFileSync("bwrap", ["--version"], { stdio: "ignore" });
+    return true;
+  } catch {
+    return false;
+  }
+}
+
+function ensureBwrap(): void {
+  if (isBwrapAvailable()) return;
+  log.info("» installing bubblewrap for the isolated bash sandbox...");
+  try {
+    execFileSync("sudo", ["-n", "apt-get", "install", "-y", "-qq", "bubblewrap"], {
+      stdio: "ignore",
+    });
+  } catch {
+    // fall through to the availability check below
+  }
+  if (!isBwrapAvailable()) {
+    throw new Error(
+      "isolated bash sandbox requires bubblewrap (bwrap), which is not installed and could not be installed"
+    );
+  }
+}
+
+// forwards the sandbox's loopback proxy port to the host-side proxy socket
+const FORWARDER_SCRIPT = `
+const net = require("node:net");
+const [socketPath, port] = process.argv.slice(1);
+net
+  .createServer((client) => {
+    const upstream = net.connect(socketPath);
+    client.pipe(upstream).pipe(client);
+    client.on("error", () => upstream.destroy());
+    upstream.on("error", () => client.destroy());
+  })
+  .listen(Number(port), "127.0.0.1", () => console.log("ready"));
+`;
+
+/**
+ * network-isolated sandbox for bash commands (linux only).
+ *
+ * commands run under bubblewrap with their own network namespace, so the only way out is an HTTP
+ * proxy served by this process on a unix socket, which only tunnels to allowlisted hosts.
+ * the filesystem is read-only except for the workspace, and /tmp is private to each command.
+ */
+export class NetworkSandbox {
+  private readonly server: Server;
+  private readonly socketDir: string;
+  private readonly socketPath: string;
+  readonly allowedHosts: string[];
+
+  // no parameter properties: node's type stripping (node play.ts) can't run them
+  private constructor(server: Server, socketDir: string, socketPath: string, allowedHosts: string[]) {
+    this.server = server;
+    this.socketDir = socketDir;
+    this.socketPath = socketPath;
+    this.allowedHosts = allowedHosts;
+  }
+
+  static async start(allowedHosts: string[]): Promise<NetworkSandbox> {
+    if (process.platform !== "linux") {
+      throw new Error("isolated bash sandbox is only supported on linux runners");
+    }
+    ensureBwrap();
+
+    const socketDir = mkdtempSync(join(tmpdir(), "pullfrog-sandbox-"));
+    const socketPath = join(socketDir, "proxy.sock");
+    const server = createServer();
+    const sandbox = new NetworkSandbox(server, socketDir, socketPath, allowedHosts);
+
+    server.on("connect", (req, socket, head) => {
+      sandbox.handleConnect(req, socket as Socket, head);
+    });
+    server.on("request", (req, res) => {
+      // plain http: only absolute-form requests to allowlisted hosts are forwarded
+      let url: URL;
+      try {
+        url = new URL(req.url ?? "");
+      } catch {
+        res.writeHead(400).end();
+        return;
+      }
+      if (url.protocol !== "http:" || !sandbox.allow(url.hostname)) {
+        res.writeHead(403).end(`pullfrog sandbox: ${url.hostname} is not allowlisted\n`);
+        return;
+      }
+      const options = { method: req.method, headers: req.headers };
+      const upstream = request(url, options, (upstreamRes) => {
+        res.writeHead(upstreamRes.statusCode ?? 502, upstreamRes.headers);
+        upstreamRes.pipe(res);
+      });
+      upstream.on("error", () => res.writeHead(502).end());
+      req.pipe(upstream);
+    });
+
+    await new Promise<void>((resolve, reject) => {
+      server.once("error", reject);
+      server.listen(socketPath, () => resolve());
+    });
+
+    log.info(`» isolated bash sandbox ready (allowed hosts: ${allowedHosts.join(", ")})`);
+    return sandbox;
+  }
+
+  private allow(host: string): boolean {
+    const allowed = isHostAllowed(host, this.allowedHosts);
+    if (!allowed) {
+      log.info(`» sandbox blocked connection to ${host}`);
+    }
+    return allowed;
+  }
+
+  private handleConnect(req: IncomingMessage, client: Socket, head: Buffer): void {
+    const { host, port } = splitHostPort(req.url ?? "", 443);
+    if (!this.allow(host) || (port !== 443 && port !== 80)) {
+      client.end("HTTP/1.1 403 Forbidden\r\n\r\n");
+      return;
+    }
+    const upstream = connect(port, host, () => {
+      client.write("HTTP/1.1 200 Connection Established\r\n\r\n");
+      if (head.length > 0) upstream.write(head);
+      upstream.pipe(client).pipe(upstream);
+    });
+    upstream.on("error", () => client.end("HTTP/1.1 502 Bad Gateway\r\n\r\n"));
+    client.on("error", () => upstream.destroy());
+  }
+
+  /**
+   * build the bwrap invocation for a command. the workspace stays writable, PULLFROG_TEMP_DIR is
+   * readable (diffs, spilled output), and proxy env vars point at the in-sandbox forwarder.
+   */
+  wrap(
+    command: string,
+    options: { workspace: string; cwd: string }
+  ): { cmd: string; args: string[] } {
+    const proxyUrl = `http://127.0.0.1:${SANDBOX_PROXY_PORT}`;
+    const tempDir = process.env.PULLFROG_TEMP_DIR;
+    const script = [
+      `exec 3< <("${process.execPath}" -e '${FORWARDER_SCRIPT}' "${this.socketPath}" ${SANDBOX_PROXY_PORT})`,
+      "read -r _ <&3",
+      command,
+    ].join("\n");
+
+    const args = [
+      "--ro-bind",
+      "/",
+      "/",
+      "--dev",
+      "/dev",
+      "--proc",
+      "/proc",
+      "--tmpfs",
+      "/tmp",
+      "--bind",
+      options.workspace,
+      options.workspace,
+      "--bind",
+      this.socketDir,
+      this.socketDir,
+      ...(tempDir ? ["--ro-bind", tempDir, tempDir] : []),
+      "--unshare-net",
+      "--unshare-pid",
+      "--die-with-parent",
+      "--chdir",
+      options.cwd,
+      ...["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy"].flatMap((name) => [
+        "--setenv",
+        name,
+        proxyUrl,
+      ]),
+      "--setenv",
+      "NO_PROXY",
+      "localhost,127.0.0.1",
+      "bash",
+      "-c",
+      script,
+    ];
+
+    return { cmd: "bwrap", args };
+  }
+
+  async close(): Promise<void> {
+    await new Promise<void>((resolve) => this.server.close(() => resolve()));
+    rmSync(this.socketDir, { recursive: true, force: true });
+  }
+}
@@ -0,0 +1,92 @@
+import { type } from 'arktype';
+import { applyModeSettings, computeModes, ModeSchema } from '../modes.ts';
+import { createTools, type ToolContext } from './server.ts';
+import { resolveDisabledTools, SelectModeTool } from './selectMode.ts';
+import type { ToolResult } from './shared.ts';
+
+function makeCtx(permissions: { bash?: string; write?: string } = {}): ToolContext {
+  const ctx = {
+    repo: { repo: { private: false } },
+    payload: { effort: 'auto', web: 'enabled', search: 'enabled', write: permissions.write ?? 'enabled', bash: permissions.bash ?? 'restricted' },
+    modes: computeModes(),
+    toolState: { progressComment: { id: null, wasUpdated: false } },
+  } as unknown as ToolContext;
+  // every tool the server registers for these permissions
+  ctx.toolState.registeredTools = createTools(ctx).map((tool) => tool.name);
+  return ctx;
+}
+
+const writeTools = ['create_pull_request', 'create_branch', 'commit_files', 'push_branch'];
+const bashTools = ['start_dependency_installation', 'await_dependency_installation', 'run_checks', 'bash', 'bash_background', 'bash_read_output', 'bash_kill'];
+
+const builtIn = (name: string) => computeModes().find((mode) => mode.name === name)!;
+
+describe('resolveDisabledTools', () => {
+  it('keeps every tool in modes without restrictions', () => {
+    expect(resolveDisabledTools(makeCtx(), builtIn('Build'))).toEqual([]);
+  });
+
+  it('drops only the branch, commit, push and PR tools in Review', () => {
+    expect(resolveDisabledTools(makeCtx(), builtIn('Review'))).toEqual(writeTools);
+  });
+
+  it('drops every tool that runs repo commands when a mode disables bash', () => {
+    expect(resolveDisabledTools(makeCtx(), builtIn('Plan'))).toEqual(bashTools);
+  });
+
+  it('drops the write tools when the run disables write', () => {
+    expect(resolveDisabledTools(makeCtx({ write: 'disabled' }), builtIn('Build'))).toEqual(writeTools);
+  });
+
+  it('never drops select_mode or report_progress', () => {
+    const ctx = makeCtx();
+    const mode = { name: 'Labels', description: 'label issues', prompt: 'label it', tools: ['get_pull_request'] };
+    expect(resolveDisabledTools(ctx, mode)).toEqual(
+      ctx.toolState.registeredTools!.filter((name) => !['select_mode', 'get_pull_request', 'report_progress'].includes(name))
+    );
+  });
+});
+
+describe('select_mode', () => {
+  it('turns tools off until another mode is selected', async () => {
+    const ctx = makeCtx();
+    const selectMode = SelectModeTool(ctx);
+    const result = (await selectMode.execute({ modeName: 'review' }, {} as never)) as ToolResult;
+    expect(result.content[0]?.text).toContain('unavailableTools');
+    expect(ctx.toolState.disabledTools).toEqual(writeTools);
+
+    await selectMode.execute({ modeName: 'Build' }, {} as never);
+    expect(ctx.toolState.disabledTools).toEqual([]);
+  });
+});
+
+describe('computeModes', () => {
+  it('only tells the agent to run checks and install dependencies when those tools exist', () => {
+    const prompts = (modes: ReturnType<typeof computeModes>) => modes.map((mode) => mode.prompt).join('\n');
+    expect(prompts(computeModes())).toContain('run_checks');
+    const withoutCommands = prompts(computeModes({ runChecks: false, dependencyInstallation: false }));
+    expect(withoutCommands).not.toMatch(/run_checks|dependency_installation/);
+  });
+});
+
+describe('applyModeSettings', () => {
+  const settings = { effort: 'auto', web: 'enabled', search: 'enabled', write: 'enabled', bash: 'restricted' } as const;
+
+  it('only narrows permissions', () => {
+    const mode = { name: 'Docs', description: '', prompt: '', permissions: { bash: 'enabled', web: 'disabled' } } as const;
+    expect(applyModeSettings(settings, mode)).toEqual({ ...settings, bash: 'restricted', web: 'disabled' });
+  });
+
+  it('replaces only the default effort', () => {
+    const mode = { name: 'Deep', description: '', prompt: '', effort: 'max' } as const;
+    expect(applyModeSettings(settings, mode).effort).toBe('max');
+    expect(applyModeSettings({ ...settings, effort: 'mini' }, mode).effort).toBe('mini');
+  });
+});
+
+describe('ModeSchema', () => {
+  it('rejects unknown permissions', () => {
+    const result = ModeSchema({ name: 'X', description: '', prompt: '', permissions: { shell: 'disabled' } });
+    expect(result).toBeInstanceOf(type.errors);
+  });
+});
@@ -1,4 +1,5 @@
 import { type } from "arktype";
+import { applyModeSettings, type Mode } from "../modes.ts";
 import type { ToolContext } from "./server.ts";
 import { execute, tool } from "./shared.ts";
 
@@ -8,6 +9,52 @@ export const SelectMode = type({
   ),
 });
 
+// available in every mode so the agent can always switch modes and report back
+const alwaysAvailableTools = ["select_mode", "report_progress"];
+
+// tools that stop working when a mode disables the permission they depend on
+const permissionTools = {
+  // run_checks and dependency installation run repo commands, so they go with bash
+  bash: [
+    "bash",
+    "bash_background",
+    "bash_read_output",
+    "bash_kill",
+    "run_checks",
+    "start_dependency_installation",
+    "await_dependency_installation",
+  ],
+  write: ["create_branch", "commit_files", "push_branch", "create_pull_request"],
+};
+
+/**
+ * registered tools a mode turns off: those missing from its tool allowlist or on its denylist,
+ * and those that depend on bash or write when the mode disables them
+ */
+export function resolveDisabledTools(ctx: ToolContext, mode: Mode): string[] {
+  const settings = applyModeSettings(ctx.payload, mode);
+  return (ctx.toolState.registeredTools ?? []).filter((name) => {
+    if (alwaysAvailableTools.includes(name)) return false;
+    if (mode.tools && !mode.tools.includes(name)) return true;
+    if (mode.disabledTools?.includes(name)) return true;
+    if (settings.bash === "disabled" && permissionTools.bash.includes(name)) return true;
+    if (settings.write === "disabled" && permissionTools.write.includes(name)) return true;
+    return false;
+  });
+}
+
+/**
+ * record the mode for the rest of the run. called by select_mode, or before the agent starts
+ * when the mode is routed from the event. returns the tools the mode turns off.
+ */
+export function applySelectedMode(ctx: ToolContext, mode: Mode): string[] {
+  // store selected mode in toolState for use by other tools (e.g., report_progress)
+  ctx.toolState.selectedMode = mode.name;
+  // tools the mode turns off stay off until another mode is selected
+  ctx.toolState.disabledTools = resolveDisabledTools(ctx, mode);
+  return ctx.toolState.disabledTools;
+}
+
 export function SelectModeTool(ctx: ToolContext) {
   return tool({
     name: "select_mode",
@@ -25,13 +72,13 @@ export function SelectModeTool(ctx: ToolContext) {
         };
       }
 
-      // store selected mode in toolState for use by other tools (e.g., report_progress)
-      ctx.toolState.selectedMode = selectedMode.name;
+      const disabledTools = applySelectedMode(ctx, selectedMode);
 
       return {
         modeName: selectedMode.name,
         description: selectedMode.description,
         prompt: selectedMode.prompt,
+        ...(disabledTools.length > 0 && { unavailableTools: disabledTools }),
       };
     }),
   });
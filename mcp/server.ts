@@ -9,11 +9,19 @@ import type { PrepResult } from "../prep/index.ts";
 import type { OctokitWithPlugins } from "../utils/github.ts";
 import type { ResolvedPayload } from "../utils/payload.ts";
 import type { RepoData } from "../utils/repoData.ts";
+import { resolveSecretsAllowlist, type SecretsAllowlist } from "../utils/secretScanner.ts";
+import type { BackgroundProcess } from "./bash.ts";
+import type { NetworkSandbox } from "./sandbox.ts";
+import type { OutboundIncident } from "./shared.ts";
 
 export interface ToolState {
   prNumber?: number;
   issueNumber?: number;
   selectedMode?: string;
+  /** names of every registered gh_pullfrog tool */
+  registeredTools?: string[];
+  /** tools turned off by the selected mode (see select_mode) */
+  disabledTools?: string[];
   review?: {
     id: number;
     nodeId: string;
@@ -28,6 +36,18 @@ export interface ToolState {
     wasUpdated: boolean;
   };
   lastProgressBody?: string;
+  /** processes started with bash_background, keyed by handle */
+  backgroundProcesses?: Map<string, BackgroundProcess>;
+  /** network-isolated sandbox for restricted bash, started on first use */
+  networkSandbox?: Promise<NetworkSandbox>;
+  /** HEAD at startup if it was the default branch as on GitHub, else null. set with the isolated sandbox */
+  defaultBranchHead?: string | null;
+  /** branches created with create_branch during this run (the only ones that may be force pushed) */
+  createdBranches?: string[];
+  /** secrets allowlist, read once from the default branch when the server starts */
+  secretsAllowlist?: SecretsAllowlist;
+  /** secrets redacted from or rejected in content posted to GitHub, reported in the job summary */
+  outboundIncidents?: OutboundIncident[];
 }
 
 import type { ResolveRunResult } from "../utils/workflow.ts";
@@ -60,7 +80,15 @@ export interface ToolContext {
   jobId: string | undefined;
 }
 
-import { BashTool } from "./bash.ts";
+import {
+  BashBackgroundTool,
+  BashKillTool,
+  BashReadOutputTool,
+  BashTool,
+  killBackgroundProcesses,
+  usesNetworkSandbox,
+} from "./bash.ts";
+import { RunChecksTool } from "./checks.ts";
 import { CheckoutPrTool } from "./checkout.ts";
 import { GetCheckSuiteLogsTool } from "./checkSuite.ts";
 import {
@@ -72,6 +100,7 @@ import {
 import { DebugShellCommandTool } from "./debug.ts";
 import {
   AwaitDependencyInstallationTool,
+  resolveDefaultBranchHead,
   StartDependencyInstallationTool,
 } from "./dependencies.ts";
 import { CommitFilesTool, CreateBranchTool, PushBranchTool } from "./git.ts";
@@ -117,21 +146,11 @@ async function findAvailablePort(startPort: number): Promise<number> {
 }
 
 /**
- * Start the MCP HTTP server and return the URL and close function
+ * every gh_pullfrog tool for this run, created from their factories with ctx
  */
-export async function startMcpHttpServer(
-  ctx: ToolContext
-): Promise<{ url: string; [Symbol.asyncDispose]: () => Promise<void> }> {
-  const server = new FastMCP({
-    name: ghPullfrogMcpName,
-    version: "0.0.1",
-  });
-
-  // create all tools as factories, passing ctx
+export function createTools(ctx: ToolContext): Tool<any, any>[] {
   const tools: Tool<any, any>[] = [
     SelectModeTool(ctx),
-    StartDependencyInstallationTool(ctx),
-    AwaitDependencyInstallationTool(ctx),
     CreateCommentTool(ctx),
     EditCommentTool(ctx),
     ReplyToReviewCommentTool(ctx),
@@ -157,14 +176,41 @@ export async function startMcpHttpServer(
   // - "enabled": native bash + MCP bash
   // - "restricted": MCP bash only (native blocked by agent)
   // - "disabled": no bash at all
+  // run_checks and dependency installation run repo commands too, so they go with bash
   const bash = ctx.payload.bash ?? "enabled";
   if (bash !== "disabled") {
-    tools.push(BashTool(ctx));
+    tools.push(
+      StartDependencyInstallationTool(ctx),
+      AwaitDependencyInstallationTool(ctx),
+      RunChecksTool(ctx),
+      BashTool(ctx),
+      BashBackgroundTool(ctx),
+      BashReadOutputTool(ctx),
+      BashKillTool(ctx)
+    );
   }
 
   tools.push(ReportProgressTool(ctx));
+  return tools;
+}
+
+/**
+ * Start the MCP HTTP server and return the URL and close function
+ */
+export async function startMcpHttpServer(
+  ctx: ToolContext
+): Promise<{ url: string; [Symbol.asyncDispose]: () => Promise<void> }> {
+  if (usesNetworkSandbox(ctx.payload)) {
+    ctx.toolState.defaultBranchHead ??= await resolveDefaultBranchHead(ctx);
+  }
+  ctx.toolState.secretsAllowlist ??= resolveSecretsAllowlist(ctx.repo.repoSettings.secretsAllowlist);
+
+  const server = new FastMCP({
+    name: ghPullfrogMcpName,
+    version: "0.0.1",
+  });
 
-  addTools(ctx, server, tools);
+  addTools(ctx, server, createTools(ctx));
 
   const port = await findAvailablePort(3764);
   const host = "127.0.0.1";
@@ -184,6 +230,8 @@ export async function startMcpHttpServer(
   return {
     url,
     [Symbol.asyncDispose]: async () => {
+      await killBackgroundProcesses(ctx);
+      await ctx.toolState.networkSandbox?.then((sandbox) => sandbox.close()).catch(() => {});
       await server.stop();
     },
   };
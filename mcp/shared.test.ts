@@ -0,0 +1,35 @@
+import type { ToolContext } from './server.ts';
+import { filterOutboundContent, formatOutboundIncidents } from './shared.ts';
+
+function makeCtx(): ToolContext {
+  return { toolState: { progressComment: { id: null, wasUpdated: false } } } as unknown as ToolContext;
+}
+
+describe('filterOutboundContent', () => {
+  afterEach(() => {
+    vi.unstubAllEnvs();
+  });
+
+  it('passes clean content through untouched', () => {
+    const ctx = makeCtx();
+    expect(filterOutboundContent(ctx, 'create_issue_comment', 'Fixed the flaky test.')).toBe('Fixed the flaky test.');
+    expect(ctx.toolState.outboundIncidents).toBeUndefined();
+  });
+
+  it('redacts the runner secret values and records the incident', () => {
+    vi.stubEnv('ANTHROPIC_API_KEY', 'runner-held-key-value');
+    const ctx = makeCtx();
+    const body = filterOutboundContent(ctx, 'report_progress', 'key was runner-held-key-value');
+    expect(body).toBe('key was [REDACTED_SECRET]');
+    expect(ctx.toolState.outboundIncidents).toEqual([expect.objectContaining({ tool: 'report_progress', action: 'redacted' })]);
+  });
+
+  it('rejects secret-looking content without echoing the secret', () => {
+    const ctx = makeCtx();
+    const token = `ghp_${'a1B2c3D4e5'.repeat(4)}`;
+    expect(() => filterOutboundContent(ctx, 'submit_review', `use ${token}`)).toThrow(/submit_review blocked[\s\S]*GitHub token \(ghp_\*\*\*\*D4e5\)/);
+    const [incident] = ctx.toolState.outboundIncidents ?? [];
+    expect(incident?.action).toBe('rejected');
+    expect(formatOutboundIncidents([incident!])).not.toContain(token);
+  });
+});
@@ -2,6 +2,12 @@ import type { StandardSchemaV1 } from "@standard-schema/spec";
 import { encode as toonEncode } from "@toon-format/toon";
 import type { FastMCP, Tool } from "fastmcp";
 import { formatJsonValue, log } from "../utils/cli.ts";
+import {
+  formatSecretFindings,
+  type SecretsAllowlist,
+  scanForSecrets,
+} from "../utils/secretScanner.ts";
+import { containsSecrets, redactSecrets } from "../utils/secrets.ts";
 import type { ToolContext } from "./server.ts";
 
 export const tool = <const params>(toolDef: Tool<any, StandardSchemaV1<params>>) => toolDef;
@@ -58,6 +64,72 @@ export const execute = <T>(
   };
 };
 
+/**
+ * a secret caught on its way to GitHub. never holds the secret itself, only masked previews.
+ */
+export interface OutboundIncident {
+  tool: string;
+  action: "redacted" | "rejected";
+  details: string;
+}
+
+/**
+ * the secrets allowlist from repo settings, resolved when the server started (empty before that)
+ */
+export function secretsAllowlist(ctx: Pick<ToolContext, "toolState">): SecretsAllowlist {
+  return ctx.toolState.secretsAllowlist ?? { values: [], paths: [] };
+}
+
+/**
+ * Filter text before a tool posts it to GitHub (comments, reviews, issues, PRs).
+ * Values of the runner's own secrets are redacted in place; other secret-looking strings
+ * are rejected so the agent can rewrite the content. Every incident is recorded for the job summary.
+ */
+export function filterOutboundContent(
+  ctx: Pick<ToolContext, "toolState">,
+  toolName: string,
+  content: string
+): string {
+  const findings = scanForSecrets(content, { allowlist: secretsAllowlist(ctx) });
+  if (findings.length > 0) {
+    recordOutboundIncident(ctx, {
+      tool: toolName,
+      action: "rejected",
+      details: formatSecretFindings(findings),
+    });
+    throw new Error(
+      `${toolName} blocked: the content appears to contain secrets:\n` +
+        `${formatSecretFindings(findings)}\n` +
+        "Remove any sensitive information (API keys, tokens, passwords) and try again."
+    );
+  }
+  if (!containsSecrets(content)) return content;
+  recordOutboundIncident(ctx, {
+    tool: toolName,
+    action: "redacted",
+    details: "  - known secret value replaced with [REDACTED_SECRET]",
+  });
+  return redactSecrets(content);
+}
+
+function recordOutboundIncident(
+  ctx: Pick<ToolContext, "toolState">,
+  incident: OutboundIncident
+): void {
+  log.warning(`» ${incident.tool}: secret ${incident.action} in outbound content`);
+  ctx.toolState.outboundIncidents = [...(ctx.toolState.outboundIncidents ?? []), incident];
+}
+
+/**
+ * markdown section for the job summary listing outbound content incidents
+ */
+export function formatOutboundIncidents(incidents: OutboundIncident[]): string {
+  const lines = incidents.map(
+    (incident) => `**${incident.tool}** (${incident.action}):\n${incident.details}`
+  );
+  return `### Secret filter\n\n${lines.join("\n\n")}`;
+}
+
 /**
  * Sanitize JSON schema to remove problematic fields that Gemini CLI/API can't handle
  * - Removes $schema field (causes "no schema with key or ref" errors)
@@ -179,14 +251,33 @@ function sanitizeTool<T extends Tool<any, any>>(tool: T): T {
   } as T;
 }
 
+/**
+ * Refuse calls to tools the selected mode has turned off.
+ * Tools stay registered so a later select_mode can turn them back on.
+ */
+function gateTool<T extends Tool<any, any>>(ctx: ToolContext, tool: T): T {
+  return {
+    ...tool,
+    execute: async (args: any, context: any) => {
+      if (ctx.toolState.disabledTools?.includes(tool.name)) {
+        return handleToolError(
+          `${tool.name} is not available in ${ctx.toolState.selectedMode} mode`
+        );
+      }
+      return tool.execute(args, context);
+    },
+  } as T;
+}
+
 export const addTools = (ctx: ToolContext, server: FastMCP, tools: Tool<any, any>[]) => {
   // sanitize schemas for gemini agent and opencode (when using Google API)
   // both have issues with draft-2020-12 schemas and any_of enum constructs
   const shouldSanitize = ctx.agent.name === "gemini" || ctx.agent.name === "opencode";
 
+  ctx.toolState.registeredTools = tools.map((tool) => tool.name);
   for (const tool of tools) {
     const processedTool = shouldSanitize ? sanitizeTool(tool) : tool;
-    server.addTool(processedTool);
+    server.addTool(gateTool(ctx, processedTool));
   }
   return server;
 };
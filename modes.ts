@@ -1,24 +1,134 @@
 import { type } from "arktype";
-import { ghPullfrogMcpName } from "./external.ts";
+import {
+  type BashPermission,
+  Effort,
+  ghPullfrogMcpName,
+  type ToolPermission,
+} from "./external.ts";
+
+/**
+ * permission overrides for a mode. they can only narrow the run's own permissions:
+ * a mode can disable bash for a run that allows it, never the reverse.
+ */
+export interface ModePermissions {
+  web?: ToolPermission | undefined;
+  search?: ToolPermission | undefined;
+  write?: ToolPermission | undefined;
+  bash?: BashPermission | undefined;
+}
 
 export interface Mode {
   name: string;
   description: string;
   prompt: string;
+  /** gh_pullfrog tools available in this mode. all tools when omitted */
+  tools?: string[] | undefined;
+  /** gh_pullfrog tools turned off in this mode, on top of `tools` */
+  disabledTools?: string[] | undefined;
+  permissions?: ModePermissions | undefined;
+  /** effort for runs that start in this mode (replaces the default "auto" effort) */
+  effort?: Effort | undefined;
 }
 
+const ToolPermissionSchema = type.enumerated("disabled", "enabled");
+
 // arktype schema for Mode validation
 export const ModeSchema = type({
   name: "string",
   description: "string",
   prompt: "string",
+  "tools?": "string[]",
+  "disabledTools?": "string[]",
+  "permissions?": {
+    "+": "reject",
+    "web?": ToolPermissionSchema,
+    "search?": ToolPermissionSchema,
+    "write?": ToolPermissionSchema,
+    "bash?": type.enumerated("disabled", "restricted", "enabled"),
+  },
+  "effort?": Effort,
 });
 
+// most to least restrictive
+const permissionOrder = ["disabled", "restricted", "enabled"] as const;
+
+function narrowPermission<permission extends BashPermission>(
+  current: permission,
+  override: permission | undefined
+): permission {
+  if (override === undefined) return current;
+  return permissionOrder.indexOf(override) < permissionOrder.indexOf(current) ? override : current;
+}
+
+interface ModeSettings {
+  effort: Effort;
+  web: ToolPermission;
+  search: ToolPermission;
+  write: ToolPermission;
+  bash: BashPermission;
+}
+
+/**
+ * apply a mode's permission overrides and effort to the run's settings.
+ * permissions only ever get narrower; the mode's effort only replaces the default "auto".
+ */
+export function applyModeSettings<settings extends ModeSettings>(
+  settings: settings,
+  mode: Mode
+): settings {
+  const current: ModeSettings = settings;
+  const permissions = mode.permissions ?? {};
+  return {
+    ...settings,
+    effort: current.effort === "auto" && mode.effort ? mode.effort : current.effort,
+    web: narrowPermission(current.web, permissions.web),
+    search: narrowPermission(current.search, permissions.search),
+    write: narrowPermission(current.write, permissions.write),
+    bash: narrowPermission(current.bash, permissions.bash),
+  };
+}
+
 const reportProgressInstruction = `Use ${ghPullfrogMcpName}/report_progress to share progress and results. Continue calling it as you make progress - it will update the same comment. Never create additional comments manually.`;
 
-const dependencyInstallationStep = `If this task will require running tests, builds, linters, or CLI commands that need installed packages, call \`${ghPullfrogMcpName}/start_dependency_installation\` NOW. This is non-blocking and allows dependencies to install in the background while you continue. Later, call \`${ghPullfrogMcpName}/await_dependency_installation\` before running commands that need them. Skip this step if only reading code or answering questions.`;
+/**
+ * gh_pullfrog tools the built-in prompts may tell the agent to call.
+ * both run repo commands, so they're only registered when bash isn't disabled
+ */
+export interface ModePromptTools {
+  runChecks: boolean;
+  dependencyInstallation: boolean;
+}
+
+const allPromptTools: ModePromptTools = { runChecks: true, dependencyInstallation: true };
+
+function dependencyInstallationStep(tools: ModePromptTools): string {
+  if (!tools.dependencyInstallation) {
+    return "Dependencies can't be installed in this run, so don't try to run commands that need them.";
+  }
+  return `If this task will require running tests, builds, linters, or CLI commands that need installed packages, call \`${ghPullfrogMcpName}/start_dependency_installation\` NOW. This is non-blocking and allows dependencies to install in the background while you continue. Later, call \`${ghPullfrogMcpName}/await_dependency_installation\` before running commands that need them. Skip this step if only reading code or answering questions.`;
+}
+
+function verificationStep(tools: ModePromptTools): string {
+  if (!tools.runChecks) {
+    return "Tests and other checks can't be run in this run. Re-read your changes against the requirements instead, and say in your summary that they weren't verified by running checks.";
+  }
+  return `Verify your changes with \`${ghPullfrogMcpName}/run_checks\`, which discovers and runs the repo's test, lint and typecheck commands and reports pass/fail per command. Await dependency installation first. Fix any failures your changes introduced before continuing.`;
+}
+
+function reviewCheckStep(tools: ModePromptTools): string {
+  if (!tools.runChecks) {
+    return "Checks can't be run in this run, so reason about whether the changes could break tests or types instead.";
+  }
+  return `If the changes could break tests or types, call \`${ghPullfrogMcpName}/await_dependency_installation\` and then \`${ghPullfrogMcpName}/run_checks\` to confirm. Only report failures that the PR introduced.`;
+}
+
+// reviews never create branches, commits or PRs. a denylist, so new read-only tools stay available
+const reviewDisabledTools = ["create_branch", "commit_files", "push_branch", "create_pull_request"];
 
-export function computeModes(): Mode[] {
+/**
+ * the built-in modes. prompts only mention the tools in `tools`, so pass what the run registers
+ */
+export function computeModes(tools: ModePromptTools = allPromptTools): Mode[] {
   return [
     {
       name: "Build",
@@ -32,7 +142,7 @@ export function computeModes(): Mode[] {
    
    Branch names must be prefixed with "pullfrog/" and be specific enough to avoid collisions. Never commit directly to main/master/production. Do NOT use git commands directly (\`git branch\`, \`git status\`, \`git log\`, etc.) - always use ${ghPullfrogMcpName} MCP tools.
 
-2. ${dependencyInstallationStep}
+2. ${dependencyInstallationStep(tools)}
 
 3. If the request requires understanding the codebase structure or conventions, gather relevant context. Read AGENTS.md if it exists. Skip this step if the prompt is trivial and self-contained.
 
@@ -40,7 +150,7 @@ export function computeModes(): Mode[] {
 
 5. Make the necessary code changes using file operations. Then use ${ghPullfrogMcpName}/commit_files to commit your changes, and ${ghPullfrogMcpName}/push_branch to push the branch. Do NOT use git commands like \`git commit\` or \`git push\` directly.
 
-6. Test your changes to ensure they work correctly
+6. ${verificationStep(tools)}
 
 7. ${reportProgressInstruction}
 
@@ -71,7 +181,7 @@ export function computeModes(): Mode[] {
       prompt: `Follow these steps. THINK HARDER.
 1. Checkout the PR using ${ghPullfrogMcpName}/checkout_pr with the PR number. This fetches the PR branch and configures push settings (including for fork PRs).
 
-2. ${dependencyInstallationStep}
+2. ${dependencyInstallationStep(tools)}
 
 3. Review the feedback provided. Understand each review comment and what changes are being requested.
    - **EVENT DATA may contain review comment details**: If available, \`approved_comments\` are comments to address, \`unapproved_comments\` are for context only. The \`triggerer\` field indicates who initiated this action - prioritize their replies when deciding how to implement fixes.
@@ -83,7 +193,7 @@ export function computeModes(): Mode[] {
 
 6. **CRITICAL: Reply to EACH review comment individually.** After fixing each comment, use ${ghPullfrogMcpName}/reply_to_review_comment to reply directly to that comment thread. Keep replies extremely brief (1 sentence max, e.g., "Fixed by renaming to X" or "Added null check"). If suggesting a small, specific, self-contained code change, use GitHub's suggestion format with \`\`\`suggestion blocks.
 
-7. Test your changes to ensure they work correctly.
+7. ${verificationStep(tools)}
 
 8. When done, commit your changes with ${ghPullfrogMcpName}/commit_files, then push with ${ghPullfrogMcpName}/push_branch. The push will automatically go to the correct remote (including fork repos). Do not create a new branch or PR - you are updating an existing one.
 
@@ -95,6 +205,7 @@ export function computeModes(): Mode[] {
       name: "Review",
       description:
         "Review code, PRs, or implementations; provide feedback or suggestions; identify issues; or check code quality, style, and correctness",
+      disabledTools: reviewDisabledTools,
       prompt: `Follow these steps to review the PR. Think hard. Do not nitpick.
 
 1. **CHECKOUT** - Call ${ghPullfrogMcpName}/checkout_pr with the PR number. This should give you all PR metadata you need, including a \`diffPath\`: a path to a temp file containing the PR diff.
@@ -106,6 +217,7 @@ export function computeModes(): Mode[] {
    - Is the approach sound? If not, focus on the approach first. Don't waste time on implementation details if the approach is wrong.
    - Can you imagine a better approach? If so, explain. Make sure it's strictly better, not just different.
    - Are there bugs, edge cases, security issues, or usability issues? Use your imagination.
+   - ${reviewCheckStep(tools)}
 
 3. **DRAFT** - For each inline comment, find the line in the diff. Each code line shows: \`| OLD | NEW | TYPE | CODE\`. Use the NEW line number (second column). When suggesting specific code changes, use GitHub's suggestion format with \`\`\`suggestion blocks to enable one-click apply. Example:
    you could simplify this
@@ -131,6 +243,7 @@ export function computeModes(): Mode[] {
       name: "Plan",
       description:
         "Create plans, break down tasks, outline steps, analyze requirements, understand scope of work, or provide task breakdowns",
+      permissions: { bash: "disabled" },
       prompt: `Follow these steps. THINK HARDER.
 1. If the request requires understanding the codebase structure or conventions, gather relevant context (read AGENTS.md if it exists). Skip this step if the prompt is trivial and self-contained.
 
@@ -151,10 +264,10 @@ export function computeModes(): Mode[] {
 
 2. If the task involves making code changes:
    - Create a branch using ${ghPullfrogMcpName}/create_branch. Branch names should be prefixed with "pullfrog/" and reflect the exact changes you are making. Never commit directly to main, master, or production.
-   - ${dependencyInstallationStep}
+   - ${dependencyInstallationStep(tools)}
    - Use file operations to create/modify files with your changes.
    - Use ${ghPullfrogMcpName}/commit_files to commit your changes, then ${ghPullfrogMcpName}/push_branch to push the branch. Do NOT use git commands directly (\`git commit\`, \`git push\`, \`git checkout\`, \`git branch\`) as these will use incorrect credentials.
-   - Test your changes to ensure they work correctly.
+   - ${verificationStep(tools)}
    - When you are done, use ${ghPullfrogMcpName}/create_pull_request to create a PR. If relevant, indicate which issue the PR addresses in the PR body (e.g. "Fixes #123"). Include links to the issue or comment that triggered the PR in the PR body.
 
 3. ${reportProgressInstruction}
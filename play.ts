@@ -89,6 +89,8 @@ Options:
 
 Environment:
   PLAY_LOCAL=1            Same as --local
+  PULLFROG_RAW_TRANSCRIPT Record the agent's raw output to this path, for test/replay.ts
+  PULLFROG_AGENT_SCRIPT   YAML/JSON script of MCP tool calls for the scripted agent (AGENT_OVERRIDE=scripted)
 
 Examples:
   tsx play.ts bash-test.ts           # Run in Docker (default)
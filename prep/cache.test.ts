@@ -0,0 +1,85 @@
+import { existsSync, mkdirSync, mkdtempSync, readFileSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import { computeCacheKey, computeCacheVersion, withDependencyCache } from './cache.ts';
+import * as cli from '../utils/cli.ts';
+
+describe('dependency cache', () => {
+  let root: string;
+  let repo: string;
+  let store: string;
+
+  beforeEach(() => {
+    root = mkdtempSync(join(tmpdir(), 'pullfrog-cache-test-'));
+    repo = join(root, 'repo');
+    store = join(root, 'store');
+    mkdirSync(repo);
+    vi.spyOn(cli.log, 'info').mockImplementation(() => {});
+    vi.spyOn(cli.log, 'warning').mockImplementation(() => {});
+    vi.stubEnv('PULLFROG_CACHE_DIR', join(root, 'cache'));
+    vi.stubEnv('GITHUB_ACTIONS', '');
+    vi.spyOn(process, 'cwd').mockReturnValue(repo);
+  });
+
+  afterEach(() => {
+    vi.restoreAllMocks();
+    vi.unstubAllEnvs();
+    rmSync(root, { recursive: true, force: true });
+  });
+
+  const spec = () => ({ cwd: repo, name: 'node-pnpm', lockfiles: ['pnpm-lock.yaml'], paths: [store] });
+
+  it('returns no key without a lockfile', () => {
+    expect(computeCacheKey(spec())).toBeNull();
+  });
+
+  it('keys on lockfile contents', () => {
+    writeFileSync(join(repo, 'pnpm-lock.yaml'), 'a');
+    const first = computeCacheKey(spec());
+    writeFileSync(join(repo, 'pnpm-lock.yaml'), 'b');
+    const second = computeCacheKey(spec());
+    expect(first).toMatch(/^pullfrog-deps-node-pnpm-/);
+    expect(first).not.toEqual(second);
+  });
+
+  it('computes the version the way actions/cache does', () => {
+    // sha256("/home/runner/.pnpm-store|gzip|1.0")
+    expect(computeCacheVersion(['/home/runner/.pnpm-store'])).toBe('829985c01093079e368ff529ae47614d89367686e10d40ffbd7c92a41869605c');
+  });
+
+  it('saves after a miss and restores on the next run', async () => {
+    writeFileSync(join(repo, 'pnpm-lock.yaml'), 'lock');
+    const install = async () => {
+      mkdirSync(store, { recursive: true });
+      writeFileSync(join(store, 'pkg.txt'), 'cached');
+      return { language: 'node' as const, packageManager: 'pnpm' as const, dependenciesInstalled: true, issues: [] };
+    };
+
+    const first = await withDependencyCache(spec(), install);
+    expect(first.cache).toMatchObject({ status: 'miss', backend: 'local' });
+
+    rmSync(store, { recursive: true });
+    const second = await withDependencyCache(spec(), async () => {
+      expect(readFileSync(join(store, 'pkg.txt'), 'utf-8')).toBe('cached');
+      return { language: 'node' as const, packageManager: 'pnpm' as const, dependenciesInstalled: true, issues: [] };
+    });
+    expect(second.cache).toMatchObject({ status: 'hit' });
+  });
+
+  it('restores an older entry as a partial hit', async () => {
+    writeFileSync(join(repo, 'pnpm-lock.yaml'), 'v1');
+    await withDependencyCache(spec(), async () => {
+      mkdirSync(store, { recursive: true });
+      writeFileSync(join(store, 'pkg.txt'), 'v1');
+      return { language: 'node' as const, packageManager: 'pnpm' as const, dependenciesInstalled: true, issues: [] };
+    });
+
+    rmSync(store, { recursive: true });
+    writeFileSync(join(repo, 'pnpm-lock.yaml'), 'v2');
+    const result = await withDependencyCache(spec(), async () => {
+      expect(existsSync(join(store, 'pkg.txt'))).toBe(true);
+      return { language: 'node' as const, packageManager: 'pnpm' as const, dependenciesInstalled: true, issues: [] };
+    });
+    expect(result.cache).toMatchObject({ status: 'partial' });
+  });
+});
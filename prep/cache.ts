@@ -0,0 +1,261 @@
+import { createHash } from "node:crypto";
+import {
+  createReadStream,
+  createWriteStream,
+  existsSync,
+  mkdirSync,
+  readdirSync,
+  readFileSync,
+  renameSync,
+  rmSync,
+  statSync,
+} from "node:fs";
+import { homedir, tmpdir } from "node:os";
+import { join, relative } from "node:path";
+import { Readable } from "node:stream";
+import { pipeline } from "node:stream/promises";
+import type { ReadableStream as NodeReadableStream } from "node:stream/web";
+import { log } from "../utils/cli.ts";
+import { spawn } from "../utils/subprocess.ts";
+import type { CacheResult, PrepResult } from "./types.ts";
+
+export interface DependencyCacheSpec {
+  /** project directory containing the lockfiles */
+  cwd: string;
+  /** cache namespace, e.g. "node-pnpm" */
+  name: string;
+  /** lockfiles (relative to cwd) hashed into the cache key; missing files are ignored */
+  lockfiles: string[];
+  /** absolute paths of package stores to restore/save */
+  paths: string[];
+}
+
+interface CacheBackend {
+  name: CacheResult["backend"];
+  /** download the archive for `key` (or the newest entry matching `restorePrefix`) into `archivePath` */
+  restore: (
+    key: string,
+    restorePrefix: string,
+    version: string,
+    archivePath: string
+  ) => Promise<string | null>;
+  save: (key: string, version: string, archivePath: string) => Promise<void>;
+}
+
+const CACHE_KEY_PREFIX = "pullfrog-deps";
+// bumped by actions/cache whenever its archive format changes
+const ACTIONS_CACHE_VERSION_SALT = "1.0";
+
+export const isCacheDisabled = () => process.env.PULLFROG_DEPENDENCY_CACHE === "false";
+
+function sha256(input: string | Buffer): string {
+  return createHash("sha256").update(input).digest("hex");
+}
+
+/**
+ * cache key: pullfrog-deps-<name>-<platform>-<arch>-<hash of lockfiles>.
+ * returns null when none of the lockfiles exist (nothing stable to key on).
+ */
+export function computeCacheKey(spec: DependencyCacheSpec): string | null {
+  const hash = createHash("sha256");
+  // monorepo projects with identical lockfiles still get separate entries
+  hash.update(relative(process.cwd(), spec.cwd));
+  let found = false;
+  for (const lockfile of spec.lockfiles) {
+    const path = join(spec.cwd, lockfile);
+    if (!existsSync(path)) continue;
+    found = true;
+    hash.update(lockfile);
+    hash.update(readFileSync(path));
+  }
+  if (!found) return null;
+  return `${restorePrefixFor(spec)}${hash.digest("hex").slice(0, 32)}`;
+}
+
+/**
+ * cache version as actions/cache computes it (`getCacheVersion` in @actions/cache): the paths,
+ * the compression method, "windowsOnly" on windows and the version salt, joined with "|"
+ */
+export function computeCacheVersion(paths: string[]): string {
+  const components = [...paths, "gzip"];
+  if (process.platform === "win32") components.push("windowsOnly");
+  components.push(ACTIONS_CACHE_VERSION_SALT);
+  return sha256(components.join("|"));
+}
+
+function restorePrefixFor(spec: DependencyCacheSpec): string {
+  return `${CACHE_KEY_PREFIX}-${spec.name}-${process.platform}-${process.arch}-`;
+}
+
+const localCacheBackend: CacheBackend = {
+  name: "local",
+  restore: async (key, restorePrefix, _version, archivePath) => {
+    const dir = localCacheDir();
+    if (!existsSync(dir)) return null;
+    const exact = join(dir, `${key}.tgz`);
+    let source = existsSync(exact) ? exact : null;
+    let matchedKey = source ? key : null;
+    if (!source) {
+      // fall back to the newest archive for the same namespace
+      const candidates = readdirSync(dir)
+        .filter((file) => file.startsWith(restorePrefix) && file.endsWith(".tgz"))
+        .map((file) => ({ file, mtime: statSync(join(dir, file)).mtimeMs }))
+        .sort((a, b) => b.mtime - a.mtime);
+      if (candidates.length === 0) return null;
+      source = join(dir, candidates[0].file);
+      matchedKey = candidates[0].file.slice(0, -".tgz".length);
+    }
+    await pipeline(createReadStream(source), createWriteStream(archivePath));
+    return matchedKey;
+  },
+  save: async (key, _version, archivePath) => {
+    const dir = localCacheDir();
+    mkdirSync(dir, { recursive: true });
+    // write then rename so concurrent runs never read a partial archive
+    const partial = join(dir, `${key}.tgz.partial-${process.pid}`);
+    await pipeline(createReadStream(archivePath), createWriteStream(partial));
+    renameSync(partial, join(dir, `${key}.tgz`));
+  },
+};
+
+function localCacheDir(): string {
+  return process.env.PULLFROG_CACHE_DIR || join(homedir(), ".cache", "pullfrog");
+}
+
+/**
+ * GitHub Actions cache service (v2, the backend used by actions/cache).
+ * entries saved here are visible to actions/cache with the same key and version.
+ */
+function actionsCacheBackend(resultsUrl: string, runtimeToken: string): CacheBackend {
+  const twirp = async <T>(method: string, body: Record<string, unknown>): Promise<T> => {
+    const url = `${resultsUrl.replace(/\/$/, "")}/twirp/github.actions.results.api.v1.CacheService/${method}`;
+    const response = await fetch(url, {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+        Authorization: `Bearer ${runtimeToken}`,
+      },
+      body: JSON.stringify(body),
+    });
+    if (!response.ok) {
+      throw new Error(`cache service ${method} failed: ${response.status} ${response.statusText}`);
+    }
+    return (await response.json()) as T;
+  };
+
+  return {
+    name: "actions",
+    restore: async (key, restorePrefix, version, archivePath) => {
+      const entry = await twirp<{ ok: boolean; signed_download_url: string; matched_key: string }>(
+        "GetCacheEntryDownloadURL",
+        { key, restore_keys: [restorePrefix], version }
+      );
+      if (!entry.ok || !entry.signed_download_url) return null;
+
+      const download = await fetch(entry.signed_download_url);
+      if (!download.ok || !download.body) {
+        throw new Error(`cache download failed: ${download.status} ${download.statusText}`);
+      }
+      await pipeline(
+        Readable.fromWeb(download.body as NodeReadableStream),
+        createWriteStream(archivePath)
+      );
+      return entry.matched_key || key;
+    },
+    save: async (key, version, archivePath) => {
+      const entry = await twirp<{ ok: boolean; signed_upload_url: string }>("CreateCacheEntry", {
+        key,
+        version,
+      });
+      // not ok usually means another job already reserved this key
+      if (!entry.ok || !entry.signed_upload_url) return;
+
+      const sizeBytes = statSync(archivePath).size;
+      const upload = await fetch(entry.signed_upload_url, {
+        method: "PUT",
+        headers: {
+          "x-ms-blob-type": "BlockBlob",
+          "Content-Length": String(sizeBytes),
+        },
+        body: Readable.toWeb(createReadStream(archivePath)) as ReadableStream,
+        duplex: "half",
+      } as RequestInit);
+      if (!upload.ok) {
+        throw new Error(`cache upload failed: ${upload.status} ${upload.statusText}`);
+      }
+
+      await twirp("FinalizeCacheEntryUpload", { key, version, size_bytes: String(sizeBytes) });
+    },
+  };
+}
+
+function resolveCacheBackend(): CacheBackend {
+  const resultsUrl = process.env.ACTIONS_RESULTS_URL;
+  const runtimeToken = process.env.ACTIONS_RUNTIME_TOKEN;
+  if (process.env.GITHUB_ACTIONS && resultsUrl && runtimeToken) {
+    return actionsCacheBackend(resultsUrl, runtimeToken);
+  }
+  return localCacheBackend;
+}
+
+async function tar(args: string[]): Promise<void> {
+  const result = await spawn({ cmd: "tar", args });
+  if (result.exitCode !== 0) {
+    throw new Error(result.stderr || `tar exited with code ${result.exitCode}`);
+  }
+}
+
+/**
+ * restore package stores before `install` runs and save them afterwards.
+ * cache failures are logged and never fail the prep step.
+ */
+export async function withDependencyCache<result extends PrepResult>(
+  spec: DependencyCacheSpec,
+  install: () => Promise<result>
+): Promise<result> {
+  const key = isCacheDisabled() ? null : computeCacheKey(spec);
+  if (!key) return install();
+
+  const backend = resolveCacheBackend();
+  const restorePrefix = restorePrefixFor(spec);
+  // paths are part of the version so a layout change never restores into the wrong place
+  const version = computeCacheVersion(spec.paths);
+  const archivePath = join(tmpdir(), `${key}-${process.pid}.tgz`);
+
+  let cache: CacheResult = { status: "miss", key, backend: backend.name };
+  try {
+    const matchedKey = await backend.restore(key, restorePrefix, version, archivePath);
+    if (matchedKey) {
+      await tar(["-xzf", archivePath, "-P"]);
+      cache = { status: matchedKey === key ? "hit" : "partial", key, backend: backend.name };
+      log.info(`» restored ${spec.name} cache (${cache.status}: ${matchedKey})`);
+    } else {
+      log.info(`» no ${spec.name} cache found for ${key}`);
+    }
+  } catch (error) {
+    log.warning(
+      `» failed to restore ${spec.name} cache: ${error instanceof Error ? error.message : String(error)}`
+    );
+  } finally {
+    rmSync(archivePath, { force: true });
+  }
+
+  const result = await install();
+
+  const existingPaths = spec.paths.filter((path) => existsSync(path));
+  if (result.dependenciesInstalled && cache.status !== "hit" && existingPaths.length > 0) {
+    try {
+      await tar(["-czf", archivePath, "-P", ...existingPaths]);
+      await backend.save(key, version, archivePath);
+      log.info(`» saved ${spec.name} cache (${key})`);
+    } catch (error) {
+      log.warning(
+        `» failed to save ${spec.name} cache: ${error instanceof Error ? error.message : String(error)}`
+      );
+    } finally {
+      rmSync(archivePath, { force: true });
+    }
+  }
+
+  return { ...result, cache };
+}
@@ -1,38 +1,91 @@
+import { join } from "node:path";
 import { log } from "../utils/cli.ts";
+import { installGoDependencies } from "./installGoDependencies.ts";
 import { installNodeDependencies } from "./installNodeDependencies.ts";
 import { installPythonDependencies } from "./installPythonDependencies.ts";
-import type { PrepDefinition, PrepResult } from "./types.ts";
+import { installRustDependencies } from "./installRustDependencies.ts";
+import { isProjectTouched, resolveProjectTargets } from "./projects.ts";
+import { runSetupSteps, validateSetupSteps } from "./setupScripts.ts";
+import type { PrepDefinition, PrepResult, SetupStep } from "./types.ts";
 
-export type { PrepResult } from "./types.ts";
+export type { PrepResult, SetupStep } from "./types.ts";
 
 // register all prep steps here
-const prepSteps: PrepDefinition[] = [installNodeDependencies, installPythonDependencies];
+const prepSteps: PrepDefinition[] = [
+  installNodeDependencies,
+  installPythonDependencies,
+  installRustDependencies,
+  installGoDependencies,
+];
+
+interface RunPrepPhaseParams {
+  /** setup steps from the `prep` section of repo settings */
+  setupSteps?: SetupStep[] | undefined;
+  /** when set, only projects containing at least one of these repo-relative paths are installed */
+  changedFiles?: string[] | undefined;
+  /** compile Rust tests after fetching crates */
+  rustPrebuild?: boolean | undefined;
+}
 
 /**
- * run all prep steps sequentially.
+ * run all prep steps sequentially for every discovered project, then repo-defined setup steps.
+ * returns one result per (language, project path).
  * failures are logged as warnings but don't stop the run.
  */
-export async function runPrepPhase(): Promise<PrepResult[]> {
+export async function runPrepPhase(params: RunPrepPhaseParams = {}): Promise<PrepResult[]> {
   log.debug("» starting prep phase...");
   const startTime = Date.now();
   const results: PrepResult[] = [];
+  const root = process.cwd();
+  const options = { rustPrebuild: params.rustPrebuild ?? false };
 
-  for (const step of prepSteps) {
-    const shouldRun = await step.shouldRun();
-    if (!shouldRun) {
-      log.debug(`» skipping ${step.name} (not applicable)`);
+  const targets = await resolveProjectTargets(root, prepSteps);
+  for (const target of targets) {
+    const label = target.path === "." ? target.step.name : `${target.step.name} (${target.path})`;
+
+    if (params.changedFiles && !isProjectTouched(target, params.changedFiles)) {
+      log.debug(`» skipping ${label} (not touched by the PR)`);
       continue;
     }
 
-    log.debug(`» running ${step.name}...`);
-    const result = await step.run();
-    results.push(result);
+    log.debug(`» running ${label}...`);
+    const result = await target.step.run(join(root, target.path), options);
+    results.push({ ...result, path: target.path });
 
     if (result.dependenciesInstalled) {
-      log.debug(`» ${step.name}: dependencies installed`);
+      log.debug(`» ${label}: dependencies installed`);
     } else if (result.issues.length > 0) {
-      log.warning(`» ${step.name}: ${result.issues[0]}`);
+      log.warning(`» ${label}: ${result.issues[0]}`);
+    }
+  }
+
+  // repo-defined setup runs last so it can rely on installed dependencies
+  let setupSteps: SetupStep[] = [];
+  try {
+    setupSteps = validateSetupSteps(params.setupSteps ?? []);
+  } catch (error) {
+    const message = error instanceof Error ? error.message : String(error);
+    log.warning(`» setup steps: ${message}`);
+    results.push({
+      language: "script",
+      name: "prep",
+      command: "",
+      skipped: true,
+      durationMs: 0,
+      dependenciesInstalled: false,
+      issues: [message],
+    });
+  }
+
+  if (setupSteps.length > 0) {
+    log.debug(`» running ${setupSteps.length} setup step(s)...`);
+    const setupResults = await runSetupSteps(setupSteps);
+    for (const result of setupResults) {
+      if (!result.dependenciesInstalled) {
+        log.warning(`» ${result.name}: ${result.issues[0]}`);
+      }
     }
+    results.push(...setupResults);
   }
 
   const totalDurationMs = Date.now() - startTime;
@@ -0,0 +1,216 @@
+import { type ChildProcess, spawn as spawnChild } from "node:child_process";
+import { existsSync, mkdirSync, readFileSync } from "node:fs";
+import { join } from "node:path";
+import { log } from "../utils/cli.ts";
+import { spawn } from "../utils/subprocess.ts";
+import { withDependencyCache } from "./cache.ts";
+import { commandOutput, isCommandAvailable } from "./shared.ts";
+import type { GoPrepResult, PrepDefinition } from "./types.ts";
+
+// go directives are repo content, so only plain release versions are ever put in a download URL
+const GO_VERSION = /^\d+\.\d+(\.\d+)?$/;
+
+// go release architectures keyed by node's process.arch
+const GO_ARCH: Record<string, string> = {
+  x64: "amd64",
+  arm64: "arm64",
+};
+
+interface GoDirectives {
+  /** `go 1.22.0` - minimum language version */
+  go: string | null;
+  /** `toolchain go1.22.3` - preferred toolchain (go >= 1.21) */
+  toolchain: string | null;
+}
+
+function readGoDirectives(path: string): GoDirectives {
+  const content = readFileSync(path, "utf-8");
+  return {
+    go: content.match(/^go\s+(\S+)/m)?.[1] ?? null,
+    toolchain: content.match(/^toolchain\s+(\S+)/m)?.[1] ?? null,
+  };
+}
+
+/**
+ * module directories from go.work `use` directives (single-line and block form)
+ */
+function getWorkspaceModules(cwd: string): string[] {
+  const goWorkPath = join(cwd, "go.work");
+  if (!existsSync(goWorkPath)) return [];
+  const content = readFileSync(goWorkPath, "utf-8");
+  const modules: string[] = [];
+  for (const match of content.matchAll(/^use\s+(?:\(([^)]*)\)|(\S+))/gm)) {
+    const entries = match[1] !== undefined ? match[1].split("\n") : [match[2]];
+    for (const entry of entries) {
+      const dir = entry.replace(/\/\/.*$/, "").trim();
+      if (dir) modules.push(dir);
+    }
+  }
+  return modules;
+}
+
+function goEnv(toolchain: string | null): NodeJS.ProcessEnv {
+  const env: NodeJS.ProcessEnv = { PATH: process.env.PATH || "", HOME: process.env.HOME || "" };
+  // respect non-default module cache locations (e.g. self-hosted runners)
+  if (process.env.GOPATH) env.GOPATH = process.env.GOPATH;
+  if (process.env.GOMODCACHE) env.GOMODCACHE = process.env.GOMODCACHE;
+  if (process.env.GOPROXY) env.GOPROXY = process.env.GOPROXY;
+  if (process.env.GOPRIVATE) env.GOPRIVATE = process.env.GOPRIVATE;
+  // setup-go exports GOTOOLCHAIN=local, which would ignore the toolchain directive
+  env.GOTOOLCHAIN = toolchain ? "auto" : process.env.GOTOOLCHAIN || "auto";
+  return env;
+}
+
+/**
+ * `curl -fsSL <url> | tar -C <dir> -xz` without a shell: both run with args arrays
+ * and curl's output is piped into tar here. returns stderr on failure, null on success.
+ */
+async function downloadAndExtract(url: string, dir: string): Promise<string | null> {
+  const env = { PATH: process.env.PATH || "", HOME: process.env.HOME || "" };
+  const curl = spawnChild("curl", ["-fsSL", url], { env, stdio: ["ignore", "pipe", "pipe"] });
+  const tar = spawnChild("tar", ["-C", dir, "-xz"], { env, stdio: ["pipe", "ignore", "pipe"] });
+  curl.stdout.pipe(tar.stdin);
+
+  let stderr = "";
+  // tar exiting early (bad archive, full disk) makes the pipe write fail with EPIPE
+  tar.stdin.on("error", (error) => {
+    stderr += `tar stopped reading the download: ${error.message}\n`;
+    curl.kill();
+  });
+  for (const child of [curl, tar]) {
+    child.stderr?.on("data", (chunk: Buffer) => {
+      stderr += chunk.toString();
+      process.stderr.write(chunk);
+    });
+  }
+  const exited = (child: ChildProcess) =>
+    new Promise<number>((resolve) => {
+      child.on("error", (error) => {
+        stderr += `${error.message}\n`;
+        resolve(1);
+      });
+      child.on("close", (code) => resolve(code ?? 1));
+    });
+  const [curlCode, tarCode] = await Promise.all([exited(curl), exited(tar)]);
+  return curlCode === 0 && tarCode === 0 ? null : stderr.trim();
+}
+
+/**
+ * install go from the official tarball into $HOME/.pullfrog/go.
+ * the go command switches to the `toolchain` directive on its own once any go >= 1.21 is present.
+ */
+async function installGo(version: string): Promise<string | null> {
+  const arch = GO_ARCH[process.arch];
+  if (!arch || (process.platform !== "linux" && process.platform !== "darwin")) {
+    return `cannot install go automatically on ${process.platform}/${process.arch}`;
+  }
+
+  if (!GO_VERSION.test(version)) {
+    return `cannot install go automatically: "${version}" is not a go release version`;
+  }
+  // go directives before 1.21 omit the patch version (e.g. "1.20"), which has no tarball
+  const tarballVersion = /^\d+\.\d+$/.test(version) ? `${version}.0` : version;
+  const installRoot = join(process.env.HOME || "", ".pullfrog");
+  mkdirSync(installRoot, { recursive: true });
+
+  log.info(`» installing go${tarballVersion}...`);
+  const url = `https://go.dev/dl/go${tarballVersion}.${process.platform}-${arch}.tar.gz`;
+  const error = await downloadAndExtract(url, installRoot);
+  if (error !== null) {
+    return error || `failed to install go${tarballVersion}`;
+  }
+
+  // add to PATH for subsequent commands (including the agent's)
+  process.env.PATH = `${join(installRoot, "go", "bin")}:${process.env.PATH}`;
+
+  log.info(`» installed go${tarballVersion}`);
+  return null;
+}
+
+export const installGoDependencies: PrepDefinition = {
+  name: "installGoDependencies",
+
+  shouldRun: (cwd) => {
+    return existsSync(join(cwd, "go.mod")) || existsSync(join(cwd, "go.work"));
+  },
+
+  workspaceMembers: getWorkspaceModules,
+
+  run: async (cwd): Promise<GoPrepResult> => {
+    const workspace = existsSync(join(cwd, "go.work"));
+    const configFile = workspace ? "go.work" : "go.mod";
+    const directives = readGoDirectives(join(cwd, configFile));
+    const toolchain = directives.toolchain;
+
+    log.info(`» detected go config: ${configFile}${toolchain ? ` (toolchain: ${toolchain})` : ""}`);
+
+    const base = { language: "go" as const, configFile, toolchain, workspace };
+
+    if (!(await isCommandAvailable("go"))) {
+      log.info(`» go not found, attempting to install...`);
+      // install the pinned toolchain directly when present, otherwise the minimum go version
+      const version = toolchain?.replace(/^go/, "") ?? directives.go;
+      if (!version) {
+        return {
+          ...base,
+          dependenciesInstalled: false,
+          issues: [`go is not installed and ${configFile} does not declare a go version`],
+        };
+      }
+      const installError = await installGo(version);
+      if (installError) {
+        return { ...base, dependenciesInstalled: false, issues: [installError] };
+      }
+    }
+
+    // keep the toolchain switch for the agent's own go commands
+    const env = goEnv(toolchain);
+    process.env.GOTOOLCHAIN = env.GOTOOLCHAIN;
+
+    const modCache =
+      process.env.GOMODCACHE ||
+      (await commandOutput("go", ["env", "GOMODCACHE"])) ||
+      join(process.env.HOME || "", "go", "pkg", "mod");
+    const cacheSpec = {
+      cwd,
+      name: "go-modules",
+      // workspace modules keep their own requirements, so their go.mod/go.sum are part of the key
+      lockfiles: workspace
+        ? [
+            "go.work",
+            "go.work.sum",
+            ...getWorkspaceModules(cwd).flatMap((dir) => [
+              join(dir, "go.mod"),
+              join(dir, "go.sum"),
+            ]),
+          ]
+        : ["go.mod", "go.sum"],
+      paths: [modCache],
+    };
+
+    return withDependencyCache(cacheSpec, async (): Promise<GoPrepResult> => {
+      log.info(`» running: go mod download`);
+      const result = await spawn({
+        cmd: "go",
+        args: ["mod", "download"],
+        env,
+        cwd,
+        onStdout: (chunk) => process.stdout.write(chunk),
+        onStderr: (chunk) => process.stderr.write(chunk),
+      });
+
+      if (result.exitCode !== 0) {
+        const output = [result.stdout, result.stderr].filter(Boolean).join("\n").trim();
+        return {
+          ...base,
+          dependenciesInstalled: false,
+          issues: [
+            `\`go mod download\` failed:\n${output || `exited with code ${result.exitCode}`}`,
+          ],
+        };
+      }
+
+      return { ...base, dependenciesInstalled: true, issues: [] };
+    });
+  },
+};
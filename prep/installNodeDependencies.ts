@@ -1,10 +1,13 @@
 import { existsSync, readFileSync } from "node:fs";
+import { homedir } from "node:os";
 import { join } from "node:path";
 import { isKeyOf } from "@ark/util";
 import { detect } from "package-manager-detector";
 import { resolveCommand } from "package-manager-detector/commands";
 import { log } from "../utils/cli.ts";
 import { spawn } from "../utils/subprocess.ts";
+import { withDependencyCache } from "./cache.ts";
+import { commandOutput, isCommandAvailable } from "./shared.ts";
 import type { NodePackageManager, NodePrepResult, PrepDefinition } from "./types.ts";
 
 // install command templates for each package manager (version placeholder: {version})
@@ -16,13 +19,43 @@ const nodePackageManagers: Record<NodePackageManager, string[]> = {
   deno: ["sh", "-c", "curl -fsSL https://deno.land/install.sh | sh"],
 };
 
-async function isCommandAvailable(command: string): Promise<boolean> {
-  const result = await spawn({
-    cmd: "which",
-    args: [command],
-    env: { PATH: process.env.PATH || "" },
-  });
-  return result.exitCode === 0;
+const NODE_LOCKFILES = [
+  "package-lock.json",
+  "npm-shrinkwrap.json",
+  "pnpm-lock.yaml",
+  "yarn.lock",
+  "bun.lockb",
+  "bun.lock",
+  "deno.lock",
+];
+
+// package store locations (cached across runs, keyed on the lockfile)
+async function getStorePaths(name: NodePackageManager): Promise<string[]> {
+  switch (name) {
+    case "npm":
+      return [join(homedir(), ".npm")];
+    case "pnpm": {
+      const storePath = await commandOutput("pnpm", ["store", "path"]);
+      return storePath ? [storePath] : [];
+    }
+    case "yarn": {
+      // berry exposes cacheFolder, classic uses `yarn cache dir`
+      const berryCache = await commandOutput("yarn", ["config", "get", "cacheFolder"]);
+      const cachePath =
+        berryCache && berryCache !== "undefined"
+          ? berryCache
+          : await commandOutput("yarn", ["cache", "dir"]);
+      return cachePath ? [cachePath] : [];
+    }
+    case "bun":
+      return [join(homedir(), ".bun", "install", "cache")];
+    case "deno":
+      return [process.env.DENO_DIR || join(homedir(), ".cache", "deno")];
+    default: {
+      const _exhaustive: never = name;
+      return _exhaustive satisfies never;
+    }
+  }
 }
 
 interface PackageManagerSpec {
@@ -30,8 +63,8 @@ interface PackageManagerSpec {
   installSpec: string; // e.g., "pnpm@8.15.0" (without hash suffix)
 }
 
-function getPackageManagerFromPackageJson(): PackageManagerSpec | null {
-  const packageJsonPath = join(process.cwd(), "package.json");
+function getPackageManagerFromPackageJson(cwd: string): PackageManagerSpec | null {
+  const packageJsonPath = join(cwd, "package.json");
   try {
     const content = readFileSync(packageJsonPath, "utf-8");
     const pkg = JSON.parse(content) as { packageManager?: string };
@@ -80,20 +113,60 @@ async function installPackageManager(
   return null;
 }
 
+/**
+ * workspace globs from package.json `workspaces` (npm/yarn/bun) or pnpm-workspace.yaml
+ */
+function getWorkspaceGlobs(cwd: string): string[] {
+  const globs: string[] = [];
+  try {
+    const pkg = JSON.parse(readFileSync(join(cwd, "package.json"), "utf-8")) as {
+      workspaces?: string[] | { packages?: string[] };
+    };
+    const workspaces = Array.isArray(pkg.workspaces) ? pkg.workspaces : pkg.workspaces?.packages;
+    globs.push(...(workspaces ?? []));
+  } catch {
+    // invalid package.json - the install will surface the error
+  }
+
+  const pnpmWorkspacePath = join(cwd, "pnpm-workspace.yaml");
+  if (existsSync(pnpmWorkspacePath)) {
+    // only the `packages:` list matters here, so avoid pulling in a yaml parser
+    let inPackages = false;
+    for (const line of readFileSync(pnpmWorkspacePath, "utf-8").split("\n")) {
+      if (/^packages:\s*$/.test(line)) {
+        inPackages = true;
+      } else if (inPackages && /^\s*-\s*/.test(line)) {
+        globs.push(
+          line
+            .replace(/^\s*-\s*/, "")
+            .replace(/\s+#.*$/, "")
+            .replace(/^["']|["']$/g, "")
+        );
+      } else if (inPackages && /^\S/.test(line)) {
+        inPackages = false;
+      }
+    }
+  }
+
+  return globs;
+}
+
 export const installNodeDependencies: PrepDefinition = {
   name: "installNodeDependencies",
 
-  shouldRun: () => {
-    const packageJsonPath = join(process.cwd(), "package.json");
+  shouldRun: (cwd) => {
+    const packageJsonPath = join(cwd, "package.json");
     return existsSync(packageJsonPath);
   },
 
-  run: async (): Promise<NodePrepResult> => {
+  workspaceMembers: getWorkspaceGlobs,
+
+  run: async (cwd): Promise<NodePrepResult> => {
     // check packageManager field in package.json first (takes priority)
-    const fromPackageJson = getPackageManagerFromPackageJson();
+    const fromPackageJson = getPackageManagerFromPackageJson(cwd);
 
     // detect from lockfile as fallback
-    const detected = await detect({ cwd: process.cwd() });
+    const detected = await detect({ cwd });
 
     // prefer package.json field, fall back to lockfile detection, default to npm
     const packageManager = fromPackageJson?.name || (detected?.name as NodePackageManager) || "npm";
@@ -133,33 +206,43 @@ export const installNodeDependencies: PrepDefinition = {
       };
     }
 
-    const fullCommand = `${resolved.command} ${resolved.args.join(" ")}`;
-    log.info(`» running: ${fullCommand}`);
-    const result = await spawn({
-      cmd: resolved.command,
-      args: resolved.args,
-      env: { PATH: process.env.PATH || "", HOME: process.env.HOME || "" },
-      onStdout: (chunk) => process.stdout.write(chunk),
-      onStderr: (chunk) => process.stderr.write(chunk),
-    });
+    const cacheSpec = {
+      cwd,
+      name: `node-${packageManager}`,
+      lockfiles: NODE_LOCKFILES,
+      paths: await getStorePaths(packageManager),
+    };
+
+    return withDependencyCache(cacheSpec, async (): Promise<NodePrepResult> => {
+      const fullCommand = `${resolved.command} ${resolved.args.join(" ")}`;
+      log.info(`» running: ${fullCommand}`);
+      const result = await spawn({
+        cmd: resolved.command,
+        args: resolved.args,
+        env: { PATH: process.env.PATH || "", HOME: process.env.HOME || "" },
+        cwd,
+        onStdout: (chunk) => process.stdout.write(chunk),
+        onStderr: (chunk) => process.stderr.write(chunk),
+      });
+
+      if (result.exitCode !== 0) {
+        // combine stdout and stderr for better error context (pnpm often outputs errors to stdout)
+        const output = [result.stdout, result.stderr].filter(Boolean).join("\n").trim();
+        const errorMessage = output || `exited with code ${result.exitCode}`;
+        return {
+          language: "node",
+          packageManager,
+          dependenciesInstalled: false,
+          issues: [`\`${fullCommand}\` failed:\n${errorMessage}`],
+        };
+      }
 
-    if (result.exitCode !== 0) {
-      // combine stdout and stderr for better error context (pnpm often outputs errors to stdout)
-      const output = [result.stdout, result.stderr].filter(Boolean).join("\n").trim();
-      const errorMessage = output || `exited with code ${result.exitCode}`;
       return {
         language: "node",
         packageManager,
-        dependenciesInstalled: false,
-        issues: [`\`${fullCommand}\` failed:\n${errorMessage}`],
+        dependenciesInstalled: true,
+        issues: [],
       };
-    }
-
-    return {
-      language: "node",
-      packageManager,
-      dependenciesInstalled: true,
-      issues: [],
-    };
+    });
   },
 };
@@ -1,17 +1,41 @@
-import { existsSync } from "node:fs";
+import { existsSync, readFileSync } from "node:fs";
+import { homedir } from "node:os";
 import { join } from "node:path";
 import { log } from "../utils/cli.ts";
 import { spawn } from "../utils/subprocess.ts";
+import { withDependencyCache } from "./cache.ts";
+import { isCommandAvailable, readTomlStringArray } from "./shared.ts";
 import type { PrepDefinition, PythonPackageManager, PythonPrepResult } from "./types.ts";
 
 interface PythonConfig {
   file: string;
   tool: PythonPackageManager;
   installCmd: string[];
+  /** extra env for the install command */
+  env?: Record<string, string>;
+  /** the tool installs into <cwd>/.venv, which is exported onto PATH afterwards */
+  venv?: boolean;
 }
 
+const VENV_DIR = ".venv";
+
 // python dependency file patterns in priority order
 const PYTHON_CONFIGS: PythonConfig[] = [
+  // lockfile-managed projects come first: they usually also have a pyproject.toml.
+  // frozen installs keep the lockfile untouched so it doesn't end up in the agent's commits
+  {
+    file: "uv.lock",
+    tool: "uv",
+    installCmd: ["uv", "sync", "--frozen"],
+    venv: true,
+  },
+  {
+    file: "pdm.lock",
+    tool: "pdm",
+    installCmd: ["pdm", "install", "--frozen-lockfile"],
+    env: { PDM_VENV_IN_PROJECT: "true" },
+    venv: true,
+  },
   {
     file: "requirements.txt",
     tool: "pip",
@@ -44,21 +68,36 @@ const PYTHON_CONFIGS: PythonConfig[] = [
   },
 ];
 
+// package caches (restored/saved around the install, keyed on the config file)
+function getCachePaths(tool: PythonPackageManager): string[] {
+  const cacheHome = process.env.XDG_CACHE_HOME || join(homedir(), ".cache");
+  const pipCache = process.env.PIP_CACHE_DIR || join(cacheHome, "pip");
+  switch (tool) {
+    case "pip":
+      return [pipCache];
+    case "pipenv":
+      return [pipCache, join(cacheHome, "pipenv")];
+    case "poetry":
+      return [join(cacheHome, "pypoetry")];
+    case "uv":
+      return [process.env.UV_CACHE_DIR || join(cacheHome, "uv")];
+    case "pdm":
+      return [join(cacheHome, "pdm")];
+    default: {
+      const _exhaustive: never = tool;
+      return _exhaustive satisfies never;
+    }
+  }
+}
+
 // tool install commands (via pip)
 const TOOL_INSTALL_COMMANDS: Record<string, string[]> = {
   pipenv: ["pip", "install", "pipenv"],
   poetry: ["pip", "install", "poetry"],
+  uv: ["pip", "install", "uv"],
+  pdm: ["pip", "install", "pdm"],
 };
 
-async function isCommandAvailable(command: string): Promise<boolean> {
-  const result = await spawn({
-    cmd: "which",
-    args: [command],
-    env: { PATH: process.env.PATH || "" },
-  });
-  return result.exitCode === 0;
-}
-
 async function installTool(name: string): Promise<string | null> {
   const installCmd = TOOL_INSTALL_COMMANDS[name];
   if (!installCmd) {
@@ -83,24 +122,44 @@ async function installTool(name: string): Promise<string | null> {
   return null;
 }
 
+/**
+ * put the venv first on PATH so the agent's python, pytest, etc. resolve to it
+ */
+function activateVenv(venvPath: string): void {
+  const binPath = join(venvPath, "bin");
+  if (!existsSync(binPath)) {
+    log.warning(`» expected virtualenv at ${venvPath}, not adding it to PATH`);
+    return;
+  }
+  process.env.VIRTUAL_ENV = venvPath;
+  process.env.PATH = `${binPath}:${process.env.PATH}`;
+  log.info(`» activated virtualenv: ${venvPath}`);
+}
+
 export const installPythonDependencies: PrepDefinition = {
   name: "installPythonDependencies",
 
-  shouldRun: async () => {
-    // check if python is available
-    const hasPython = (await isCommandAvailable("python3")) || (await isCommandAvailable("python"));
-    if (!hasPython) {
+  shouldRun: async (cwd) => {
+    // check if any python config file exists
+    if (!PYTHON_CONFIGS.some((config) => existsSync(join(cwd, config.file)))) {
       return false;
     }
 
-    // check if any python config file exists
-    const cwd = process.cwd();
-    return PYTHON_CONFIGS.some((config) => existsSync(join(cwd, config.file)));
+    // check if python is available
+    return (await isCommandAvailable("python3")) || (await isCommandAvailable("python"));
   },
 
-  run: async (): Promise<PythonPrepResult> => {
-    const cwd = process.cwd();
+  // uv workspaces install all members from the root
+  workspaceMembers: (cwd) => {
+    const pyprojectPath = join(cwd, "pyproject.toml");
+    if (!existsSync(pyprojectPath)) return [];
+    const content = readFileSync(pyprojectPath, "utf-8");
+    const members = readTomlStringArray(content, "tool.uv.workspace", "members") ?? [];
+    const exclude = readTomlStringArray(content, "tool.uv.workspace", "exclude") ?? [];
+    return [...members, ...exclude.map((glob) => `!${glob}`)];
+  },
 
+  run: async (cwd): Promise<PythonPrepResult> => {
     // find the first matching config
     const config = PYTHON_CONFIGS.find((c) => existsSync(join(cwd, c.file)));
     if (!config) {
@@ -131,32 +190,46 @@ export const installPythonDependencies: PrepDefinition = {
       }
     }
 
-    // run the install command
-    const [cmd, ...args] = config.installCmd;
-    log.info(`» running: ${cmd} ${args.join(" ")}`);
-    const result = await spawn({
-      cmd,
-      args,
-      env: { PATH: process.env.PATH || "", HOME: process.env.HOME || "" },
-      onStderr: (chunk) => process.stderr.write(chunk),
-    });
+    const cacheSpec = {
+      cwd,
+      name: `python-${config.tool}`,
+      lockfiles: [config.file],
+      paths: getCachePaths(config.tool),
+    };
+
+    return withDependencyCache(cacheSpec, async (): Promise<PythonPrepResult> => {
+      // run the install command
+      const [cmd, ...args] = config.installCmd;
+      log.info(`» running: ${cmd} ${args.join(" ")}`);
+      const result = await spawn({
+        cmd,
+        args,
+        env: { PATH: process.env.PATH || "", HOME: process.env.HOME || "", ...config.env },
+        cwd,
+        onStderr: (chunk) => process.stderr.write(chunk),
+      });
+
+      if (result.exitCode !== 0) {
+        return {
+          language: "python",
+          packageManager: config.tool,
+          configFile: config.file,
+          dependenciesInstalled: false,
+          issues: [result.stderr || `${cmd} exited with code ${result.exitCode}`],
+        };
+      }
+
+      if (config.venv) {
+        activateVenv(join(cwd, VENV_DIR));
+      }
 
-    if (result.exitCode !== 0) {
       return {
         language: "python",
         packageManager: config.tool,
         configFile: config.file,
-        dependenciesInstalled: false,
-        issues: [result.stderr || `${cmd} exited with code ${result.exitCode}`],
+        dependenciesInstalled: true,
+        issues: [],
       };
-    }
-
-    return {
-      language: "python",
-      packageManager: config.tool,
-      configFile: config.file,
-      dependenciesInstalled: true,
-      issues: [],
-    };
+    });
   },
 };
@@ -0,0 +1,57 @@
+import { chmodSync, mkdirSync, mkdtempSync, readFileSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import * as cli from '../utils/cli.ts';
+import { installRustDependencies } from './installRustDependencies.ts';
+
+describe('installRustDependencies', () => {
+  let root: string;
+  let project: string;
+  let cargoLog: string;
+
+  beforeEach(() => {
+    root = mkdtempSync(join(tmpdir(), 'pullfrog-rust-test-'));
+    project = join(root, 'project');
+    cargoLog = join(root, 'cargo.log');
+    mkdirSync(project);
+
+    // a stand-in cargo that records its arguments
+    const bin = join(root, 'bin');
+    mkdirSync(bin);
+    writeFileSync(join(bin, 'cargo'), `#!/bin/sh\necho "$@" >> '${cargoLog}'\n`);
+    chmodSync(join(bin, 'cargo'), 0o755);
+    vi.stubEnv('PATH', `${bin}:${process.env.PATH}`);
+    vi.stubEnv('PULLFROG_DEPENDENCY_CACHE', 'false');
+
+    vi.spyOn(cli.log, 'info').mockImplementation(() => {});
+    vi.spyOn(process.stdout, 'write').mockImplementation(() => true);
+  });
+
+  afterEach(() => {
+    vi.restoreAllMocks();
+    vi.unstubAllEnvs();
+    rmSync(root, { recursive: true, force: true });
+  });
+
+  const cargoCalls = () => readFileSync(cargoLog, 'utf-8').trim().split('\n');
+
+  it('runs only for directories with a Cargo.toml', () => {
+    expect(installRustDependencies.shouldRun(project)).toBe(false);
+    writeFileSync(join(project, 'Cargo.toml'), '[package]\nname = "demo"\n');
+    expect(installRustDependencies.shouldRun(project)).toBe(true);
+  });
+
+  it('only fetches crates by default', async () => {
+    writeFileSync(join(project, 'Cargo.toml'), '[package]\nname = "demo"\n');
+    const result = await installRustDependencies.run(project, { rustPrebuild: false });
+    expect(result).toMatchObject({ language: 'rust', dependenciesInstalled: true, prebuilt: false, issues: [] });
+    expect(cargoCalls()).toEqual(['fetch']);
+  });
+
+  it('compiles tests after fetching with rustPrebuild', async () => {
+    writeFileSync(join(project, 'Cargo.toml'), '[workspace]\nmembers = ["crates/*"]\n');
+    const result = await installRustDependencies.run(project, { rustPrebuild: true });
+    expect(result).toMatchObject({ language: 'rust', workspace: true, dependenciesInstalled: true, prebuilt: true });
+    expect(cargoCalls()).toEqual(['fetch', 'build --tests']);
+  });
+});
@@ -0,0 +1,169 @@
+import { existsSync, readFileSync } from "node:fs";
+import { join } from "node:path";
+import { log } from "../utils/cli.ts";
+import { spawn } from "../utils/subprocess.ts";
+import { withDependencyCache } from "./cache.ts";
+import { isCommandAvailable, readTomlStringArray } from "./shared.ts";
+import type { PrepDefinition, RustPrepResult } from "./types.ts";
+
+// toolchain files in priority order (rustup prefers the legacy file when both exist)
+const TOOLCHAIN_FILES = ["rust-toolchain", "rust-toolchain.toml"];
+
+function rustEnv(): NodeJS.ProcessEnv {
+  const env: NodeJS.ProcessEnv = { PATH: process.env.PATH || "", HOME: process.env.HOME || "" };
+  // respect non-default install locations (e.g. self-hosted runners)
+  if (process.env.CARGO_HOME) env.CARGO_HOME = process.env.CARGO_HOME;
+  if (process.env.RUSTUP_HOME) env.RUSTUP_HOME = process.env.RUSTUP_HOME;
+  return env;
+}
+
+/**
+ * read the pinned channel from rust-toolchain(.toml).
+ * the legacy `rust-toolchain` file may contain either a bare channel name or toml.
+ */
+function getPinnedToolchain(cwd: string): string | null {
+  for (const file of TOOLCHAIN_FILES) {
+    const path = join(cwd, file);
+    if (!existsSync(path)) continue;
+    const content = readFileSync(path, "utf-8");
+    const channelMatch = content.match(/^\s*channel\s*=\s*["']([^"']+)["']/m);
+    if (channelMatch) return channelMatch[1];
+    const bare = content.trim();
+    if (bare && !bare.includes("\n") && !bare.includes("=")) return bare;
+  }
+  return null;
+}
+
+function isCargoWorkspace(cwd: string): boolean {
+  const content = readFileSync(join(cwd, "Cargo.toml"), "utf-8");
+  return /^\s*\[workspace\]/m.test(content);
+}
+
+function getWorkspaceMembers(cwd: string): string[] {
+  const content = readFileSync(join(cwd, "Cargo.toml"), "utf-8");
+  const members = readTomlStringArray(content, "workspace", "members") ?? [];
+  const exclude = readTomlStringArray(content, "workspace", "exclude") ?? [];
+  return [...members, ...exclude.map((glob) => `!${glob}`)];
+}
+
+async function installRustup(): Promise<string | null> {
+  log.info(`» installing rustup...`);
+  const result = await spawn({
+    cmd: "sh",
+    args: [
+      "-c",
+      "curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y --profile minimal",
+    ],
+    env: rustEnv(),
+    onStderr: (chunk) => process.stderr.write(chunk),
+  });
+
+  if (result.exitCode !== 0) {
+    return result.stderr || "failed to install rustup";
+  }
+
+  // rustup installs to $CARGO_HOME/bin (default $HOME/.cargo/bin) - add to PATH for subsequent commands
+  const cargoBin = join(process.env.CARGO_HOME || join(process.env.HOME || "", ".cargo"), "bin");
+  process.env.PATH = `${cargoBin}:${process.env.PATH}`;
+
+  log.info(`» installed rustup`);
+  return null;
+}
+
+async function runCargo(cwd: string, args: string[]): Promise<string | null> {
+  const fullCommand = `cargo ${args.join(" ")}`;
+  log.info(`» running: ${fullCommand}`);
+  const result = await spawn({
+    cmd: "cargo",
+    args,
+    env: rustEnv(),
+    cwd,
+    onStdout: (chunk) => process.stdout.write(chunk),
+    onStderr: (chunk) => process.stderr.write(chunk),
+  });
+
+  if (result.exitCode !== 0) {
+    // cargo reports errors on stderr but build scripts may write to stdout
+    const output = [result.stdout, result.stderr].filter(Boolean).join("\n").trim();
+    return `\`${fullCommand}\` failed:\n${output || `exited with code ${result.exitCode}`}`;
+  }
+  return null;
+}
+
+export const installRustDependencies: PrepDefinition = {
+  name: "installRustDependencies",
+
+  shouldRun: (cwd) => {
+    return existsSync(join(cwd, "Cargo.toml"));
+  },
+
+  workspaceMembers: getWorkspaceMembers,
+
+  run: async (cwd, options): Promise<RustPrepResult> => {
+    const toolchain = getPinnedToolchain(cwd);
+    const workspace = isCargoWorkspace(cwd);
+
+    log.info(
+      `» detected cargo ${workspace ? "workspace" : "package"}${toolchain ? ` (toolchain: ${toolchain})` : ""}`
+    );
+
+    const base = { language: "rust" as const, toolchain, workspace, prebuilt: false };
+
+    if (!(await isCommandAvailable("rustup")) && !(await isCommandAvailable("cargo"))) {
+      log.info(`» cargo not found, attempting to install...`);
+      const installError = await installRustup();
+      if (installError) {
+        return { ...base, dependenciesInstalled: false, issues: [installError] };
+      }
+    }
+
+    // install the pinned toolchain explicitly so fetch/build don't stall on an implicit install
+    if (toolchain && (await isCommandAvailable("rustup"))) {
+      log.info(`» installing toolchain ${toolchain}...`);
+      const result = await spawn({
+        cmd: "rustup",
+        args: ["toolchain", "install", toolchain, "--profile", "minimal"],
+        env: rustEnv(),
+        cwd,
+        onStderr: (chunk) => process.stderr.write(chunk),
+      });
+      if (result.exitCode !== 0) {
+        return {
+          ...base,
+          dependenciesInstalled: false,
+          issues: [result.stderr || `failed to install toolchain ${toolchain}`],
+        };
+      }
+    }
+
+    // registry index, crate sources and git checkouts (not target/ - it is large and toolchain-specific)
+    const cargoHome = process.env.CARGO_HOME || join(process.env.HOME || "", ".cargo");
+    const cacheSpec = {
+      cwd,
+      name: "rust-cargo",
+      lockfiles: ["Cargo.lock"],
+      paths: [join(cargoHome, "registry"), join(cargoHome, "git")],
+    };
+
+    return withDependencyCache(cacheSpec, async (): Promise<RustPrepResult> => {
+      const fetchError = await runCargo(cwd, ["fetch"]);
+      if (fetchError) {
+        return { ...base, dependenciesInstalled: false, issues: [fetchError] };
+      }
+
+      // rustPrebuild compiles tests up front so the agent's first `cargo test` is fast
+      if (!options.rustPrebuild) {
+        return { ...base, dependenciesInstalled: true, issues: [] };
+      }
+
+      // a failed pre-build is not fatal: dependencies are fetched, the agent can fix the build
+      const buildError = await runCargo(cwd, ["build", "--tests"]);
+      return {
+        ...base,
+        dependenciesInstalled: true,
+        prebuilt: buildError === null,
+        issues: buildError ? [buildError] : [],
+      };
+    });
+  },
+};
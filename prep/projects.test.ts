@@ -0,0 +1,48 @@
+import { execFileSync } from 'node:child_process';
+import { mkdirSync, mkdtempSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join, relative } from 'node:path';
+import { installNodeDependencies } from './installNodeDependencies.ts';
+import { findProjectDirs, isProjectTouched } from './projects.ts';
+
+describe('findProjectDirs', () => {
+  let repo: string;
+
+  const write = (path: string, content = '{}') => {
+    mkdirSync(join(repo, path, '..'), { recursive: true });
+    writeFileSync(join(repo, path), content);
+  };
+
+  beforeEach(() => {
+    repo = mkdtempSync(join(tmpdir(), 'pullfrog-projects-test-'));
+    execFileSync('git', ['init', '-q'], { cwd: repo });
+  });
+
+  afterEach(() => {
+    rmSync(repo, { recursive: true, force: true });
+  });
+
+  it('skips fixtures, examples and gitignored directories', () => {
+    write('package.json');
+    write('packages/api/package.json');
+    write('test/fixtures/broken/package.json');
+    write('examples/demo/package.json');
+    write('generated/client/package.json');
+    write('.gitignore', 'generated/\n');
+
+    expect(findProjectDirs(repo).map((dir) => relative(repo, dir) || '.')).toEqual(['.', 'packages/api']);
+  });
+});
+
+describe('isProjectTouched', () => {
+  const target = (path: string) => ({ step: installNodeDependencies, path, members: [] });
+
+  it('always installs the repo root', () => {
+    expect(isProjectTouched(target('.'), [])).toBe(true);
+  });
+
+  it('installs nested projects only when files under them changed', () => {
+    expect(isProjectTouched(target('packages/api'), ['packages/api/src/index.ts'])).toBe(true);
+    expect(isProjectTouched(target('packages/api'), ['packages/web/src/index.ts', 'packages/api-client/index.ts'])).toBe(false);
+  });
+});
@@ -0,0 +1,157 @@
+import { spawnSync } from "node:child_process";
+import { type Dirent, existsSync, readdirSync } from "node:fs";
+import { join, relative, sep } from "node:path";
+import { matchesGlobs } from "../utils/glob.ts";
+import type { PrepDefinition } from "./types.ts";
+
+// files that make a directory a candidate project for at least one prep step
+const PROJECT_MARKERS = [
+  "package.json",
+  "pyproject.toml",
+  "requirements.txt",
+  "Pipfile",
+  "Pipfile.lock",
+  "poetry.lock",
+  "setup.py",
+  "Cargo.toml",
+  "go.mod",
+  "go.work",
+];
+
+// never descend into dependency, build or tool directories
+const IGNORED_DIRS = new Set([
+  "node_modules",
+  "target",
+  "dist",
+  "build",
+  "vendor",
+  "venv",
+  "__pycache__",
+]);
+
+// manifests in these are test inputs and samples, not projects the repo needs installed
+const NON_PROJECT_DIRS = new Set(["fixtures", "__fixtures__", "examples"]);
+
+const MAX_DEPTH = 4;
+
+export interface ProjectTarget {
+  step: PrepDefinition;
+  /** project directory relative to the repo root ("." for the root) */
+  path: string;
+  /** workspace member globs (relative to path) installed together with this project */
+  members: string[];
+}
+
+function toRepoPath(root: string, dir: string): string {
+  const rel = relative(root, dir);
+  return rel === "" ? "." : rel.split(sep).join("/");
+}
+
+/**
+ * the directories among `dirs` that git ignores (generated code, caches, local checkouts).
+ * empty when root is not a git checkout.
+ */
+function findGitIgnored(root: string, dirs: string[]): Set<string> {
+  if (dirs.length === 0) return new Set();
+  const result = spawnSync("git", ["check-ignore", "--stdin"], {
+    cwd: root,
+    input: dirs.map((dir) => relative(root, dir)).join("\n"),
+    encoding: "utf-8",
+  });
+  // exit code 1 means nothing is ignored, 128 that root is not a git checkout
+  if (result.status !== 0) return new Set();
+  return new Set(result.stdout.split("\n").filter(Boolean).map((path) => join(root, path)));
+}
+
+/**
+ * find directories that look like projects (root first, then breadth-first down to MAX_DEPTH).
+ * hidden, gitignored, fixture and example directories are skipped.
+ */
+export function findProjectDirs(root: string): string[] {
+  const dirs: string[] = [];
+  let level = [root];
+  for (let depth = 0; depth <= MAX_DEPTH && level.length > 0; depth++) {
+    const next: string[] = [];
+    for (const dir of level) {
+      if (PROJECT_MARKERS.some((marker) => existsSync(join(dir, marker)))) {
+        dirs.push(dir);
+      }
+      let entries: Dirent[];
+      try {
+        entries = readdirSync(dir, { withFileTypes: true });
+      } catch {
+        continue;
+      }
+      for (const entry of entries) {
+        if (
+          !entry.isDirectory() ||
+          entry.name.startsWith(".") ||
+          IGNORED_DIRS.has(entry.name) ||
+          NON_PROJECT_DIRS.has(entry.name)
+        ) {
+          continue;
+        }
+        next.push(join(dir, entry.name));
+      }
+    }
+    const ignored = findGitIgnored(root, next);
+    level = next.filter((dir) => !ignored.has(dir)).sort();
+  }
+  return dirs;
+}
+
+/**
+ * resolve which (step, project) pairs to install.
+ * projects covered by an enclosing workspace of the same step (pnpm/yarn/npm workspaces,
+ * uv workspaces, cargo workspaces, go.work) are installed through that workspace root only.
+ */
+export async function resolveProjectTargets(
+  root: string,
+  steps: PrepDefinition[]
+): Promise<ProjectTarget[]> {
+  const dirs = findProjectDirs(root);
+  const targets: ProjectTarget[] = [];
+
+  for (const step of steps) {
+    const applicable: ProjectTarget[] = [];
+    for (const dir of dirs) {
+      if (await step.shouldRun(dir)) {
+        applicable.push({
+          step,
+          path: toRepoPath(root, dir),
+          members: step.workspaceMembers?.(dir) ?? [],
+        });
+      }
+    }
+
+    for (const target of applicable) {
+      const coveringWorkspace = applicable.find(
+        (other) =>
+          other !== target &&
+          other.members.length > 0 &&
+          isMember(target.path, other.path, other.members)
+      );
+      if (!coveringWorkspace) {
+        targets.push(target);
+      }
+    }
+  }
+
+  return targets;
+}
+
+function isMember(path: string, workspacePath: string, members: string[]): boolean {
+  if (workspacePath !== "." && !path.startsWith(`${workspacePath}/`)) return false;
+  const relativePath = workspacePath === "." ? path : path.slice(workspacePath.length + 1);
+  return matchesGlobs(relativePath, members);
+}
+
+/**
+ * check whether any changed file lives under the project directory.
+ * workspace members are nested below their root, so changes to members count for the root too.
+ * the repo root always counts as touched: nested projects usually depend on it.
+ */
+export function isProjectTouched(target: ProjectTarget, changedFiles: string[]): boolean {
+  if (target.path === ".") return true;
+  return changedFiles.some((file) => file.startsWith(`${target.path}/`));
+}
@@ -0,0 +1,71 @@
+import { runSetupSteps } from './setupScripts.ts';
+
+describe('runSetupSteps', () => {
+  beforeEach(() => {
+    vi.spyOn(process.stdout, 'write').mockImplementation(() => true);
+    vi.spyOn(process.stderr, 'write').mockImplementation(() => true);
+  });
+
+  afterEach(() => {
+    vi.restoreAllMocks();
+  });
+
+  it('runs steps in declaration order by default', async () => {
+    const results = await runSetupSteps([
+      { name: 'first', run: 'true' },
+      { name: 'second', run: 'true' },
+    ]);
+    expect(results.map((r) => [r.name, r.dependenciesInstalled])).toEqual([
+      ['first', true],
+      ['second', true],
+    ]);
+  });
+
+  it('skips dependents of a failed step', async () => {
+    const results = await runSetupSteps([
+      { name: 'db', run: 'exit 3' },
+      { name: 'migrate', run: 'true' },
+      { name: 'lint', run: 'true', needs: [] },
+    ]);
+    expect(results[0]).toMatchObject({ dependenciesInstalled: false, skipped: false });
+    expect(results[0].issues[0]).toContain('exited with code 3');
+    expect(results[1]).toMatchObject({ skipped: true, issues: ['skipped because db did not succeed'] });
+    expect(results[2]).toMatchObject({ dependenciesInstalled: true });
+  });
+
+  it('runs independent steps in parallel', async () => {
+    const start = Date.now();
+    const results = await runSetupSteps([
+      { name: 'a', run: 'sleep 0.5', needs: [] },
+      { name: 'b', run: 'sleep 0.5', needs: [] },
+    ]);
+    expect(results.every((r) => r.dependenciesInstalled)).toBe(true);
+    expect(Date.now() - start).toBeLessThan(900);
+  });
+
+  it('reports timeouts as failures', async () => {
+    const [result] = await runSetupSteps([{ name: 'slow', run: 'sleep 5', timeout: 100 }]);
+    expect(result.dependenciesInstalled).toBe(false);
+    expect(result.issues[0]).toContain('timed out');
+  });
+
+  it('passes step env to the command', async () => {
+    const [result] = await runSetupSteps([
+      { name: 'env', run: 'test "$DB_URL" = postgres://local', env: { DB_URL: 'postgres://local' } },
+    ]);
+    expect(result.dependenciesInstalled).toBe(true);
+  });
+
+  it('skips unknown dependencies and cycles', async () => {
+    const results = await runSetupSteps([
+      { name: 'a', run: 'true', needs: ['missing'] },
+      { name: 'b', run: 'true', needs: ['c'] },
+      { name: 'c', run: 'true', needs: ['b'] },
+    ]);
+    expect(results.map((r) => r.issues[0])).toEqual([
+      'unknown dependency: missing',
+      'dependency cycle',
+      'dependency cycle',
+    ]);
+  });
+});
@@ -0,0 +1,148 @@
+import { resolve } from "node:path";
+import { type } from "arktype";
+import { log } from "../utils/cli.ts";
+import { spawn } from "../utils/subprocess.ts";
+import type { ScriptPrepResult, SetupStep } from "./types.ts";
+
+const DEFAULT_STEP_TIMEOUT_MS = 10 * 60 * 1000;
+
+// arktype schema for SetupStep validation
+export const SetupStepSchema = type({
+  name: "string > 0",
+  run: "string > 0",
+  "timeout?": "number > 0",
+  "env?": "Record<string, string>",
+  "cwd?": "string",
+  "needs?": "string[]",
+});
+
+export class SetupConfigError extends Error {}
+
+/**
+ * check the `prep` steps from repo settings before running them.
+ * throws SetupConfigError if step names are not unique.
+ */
+export function validateSetupSteps(steps: SetupStep[]): SetupStep[] {
+  const seen = new Set<string>();
+  for (const step of steps) {
+    if (seen.has(step.name)) {
+      throw new SetupConfigError(`duplicate setup step name: ${step.name}`);
+    }
+    seen.add(step.name);
+  }
+  return steps;
+}
+
+function skipped(step: SetupStep, reason: string): ScriptPrepResult {
+  return {
+    language: "script",
+    name: step.name,
+    command: step.run,
+    skipped: true,
+    durationMs: 0,
+    dependenciesInstalled: false,
+    issues: [reason],
+  };
+}
+
+async function runStep(step: SetupStep): Promise<ScriptPrepResult> {
+  const timeout = step.timeout ?? DEFAULT_STEP_TIMEOUT_MS;
+  const base = { language: "script" as const, name: step.name, command: step.run, skipped: false };
+  const startTime = Date.now();
+
+  log.info(`» [${step.name}] running: ${step.run}`);
+  try {
+    const result = await spawn({
+      cmd: "bash",
+      args: ["-c", step.run],
+      // same minimal env as language installs, plus whatever the step declares
+      env: { PATH: process.env.PATH || "", HOME: process.env.HOME || "", ...step.env },
+      cwd: step.cwd ? resolve(process.cwd(), step.cwd) : process.cwd(),
+      timeout,
+      onStdout: (chunk) => process.stdout.write(chunk),
+      onStderr: (chunk) => process.stderr.write(chunk),
+    });
+
+    if (result.exitCode !== 0) {
+      const output = [result.stdout, result.stderr].filter(Boolean).join("\n").trim();
+      return {
+        ...base,
+        durationMs: result.durationMs,
+        dependenciesInstalled: false,
+        issues: [`\`${step.run}\` failed:\n${output || `exited with code ${result.exitCode}`}`],
+      };
+    }
+
+    return { ...base, durationMs: result.durationMs, dependenciesInstalled: true, issues: [] };
+  } catch (error) {
+    // spawn rejects only on timeout
+    return {
+      ...base,
+      durationMs: Date.now() - startTime,
+      dependenciesInstalled: false,
+      issues: [error instanceof Error ? error.message : String(error)],
+    };
+  }
+}
+
+/**
+ * run setup steps as a dependency graph: each step starts as soon as everything it `needs` succeeded.
+ * steps whose dependencies failed (or can never run) are reported as skipped.
+ * results are returned in declaration order.
+ */
+export async function runSetupSteps(steps: SetupStep[]): Promise<ScriptPrepResult[]> {
+  const names = new Set(steps.map((s) => s.name));
+  const graph = steps.map((step, i) => ({
+    step,
+    needs: step.needs ?? (i > 0 ? [steps[i - 1].name] : []),
+  }));
+
+  const results = new Map<string, ScriptPrepResult>();
+  const running = new Map<string, Promise<void>>();
+
+  while (results.size < steps.length) {
+    let scheduled = false;
+
+    for (const { step, needs } of graph) {
+      if (results.has(step.name) || running.has(step.name)) continue;
+
+      const unknown = needs.find((n) => !names.has(n));
+      if (unknown) {
+        results.set(step.name, skipped(step, `unknown dependency: ${unknown}`));
+        scheduled = true;
+        continue;
+      }
+
+      const failed = needs.find((n) => results.get(n)?.dependenciesInstalled === false);
+      if (failed) {
+        results.set(step.name, skipped(step, `skipped because ${failed} did not succeed`));
+        scheduled = true;
+        continue;
+      }
+
+      if (needs.every((n) => results.has(n))) {
+        running.set(
+          step.name,
+          runStep(step).then((result) => {
+            results.set(step.name, result);
+            running.delete(step.name);
+          })
+        );
+        scheduled = true;
+      }
+    }
+
+    if (running.size > 0) {
+      await Promise.race(running.values());
+    } else if (!scheduled) {
+      // nothing running and nothing became ready: the remaining steps form a cycle
+      for (const { step } of graph) {
+        if (!results.has(step.name)) {
+          results.set(step.name, skipped(step, "dependency cycle"));
+        }
+      }
+    }
+  }
+
+  return steps.map((step) => results.get(step.name)!);
+}
@@ -0,0 +1,42 @@
+import { spawn } from "../utils/subprocess.ts";
+
+export async function isCommandAvailable(command: string): Promise<boolean> {
+  const result = await spawn({
+    cmd: "which",
+    args: [command],
+    env: { PATH: process.env.PATH || "" },
+  });
+  return result.exitCode === 0;
+}
+
+/**
+ * run a command and return its trimmed stdout, or null if it fails or prints nothing
+ */
+export async function commandOutput(cmd: string, args: string[]): Promise<string | null> {
+  const result = await spawn({
+    cmd,
+    args,
+    env: { PATH: process.env.PATH || "", HOME: process.env.HOME || "" },
+  });
+  const output = result.stdout.trim();
+  return result.exitCode === 0 && output ? output : null;
+}
+
+/**
+ * read a string array (e.g. `members = ["crates/*"]`) from a toml table without a full toml parser.
+ * returns null when the table or key is missing.
+ */
+export function readTomlStringArray(content: string, table: string, key: string): string[] | null {
+  const escapedTable = table.replace(/[.[\]]/g, "\\$&");
+  const tableMatch = content.match(new RegExp(`^\\s*\\[${escapedTable}\\]\\s*$`, "m"));
+  if (!tableMatch || tableMatch.index === undefined) return null;
+
+  // the table body ends at the next table header
+  const rest = content.slice(tableMatch.index + tableMatch[0].length);
+  const nextTable = rest.search(/^\s*\[/m);
+  const body = nextTable === -1 ? rest : rest.slice(0, nextTable);
+
+  const arrayMatch = body.match(new RegExp(`^\\s*${key}\\s*=\\s*\\[([\\s\\S]*?)\\]`, "m"));
+  if (!arrayMatch) return null;
+  return [...arrayMatch[1].matchAll(/["']([^"']+)["']/g)].map((m) => m[1]);
+}
@@ -1,6 +1,17 @@
+export interface CacheResult {
+  /** "partial" means an older entry for the same package manager was restored */
+  status: "hit" | "partial" | "miss";
+  key: string;
+  backend: "local" | "actions";
+}
+
 interface PrepResultBase {
+  /** project directory relative to the repo root ("." for the root), set by runPrepPhase */
+  path?: string | undefined;
   dependenciesInstalled: boolean;
   issues: string[];
+  /** dependency cache status (absent when caching is disabled or there is no lockfile) */
+  cache?: CacheResult | undefined;
 }
 
 export type NodePackageManager = "npm" | "pnpm" | "yarn" | "bun" | "deno";
@@ -10,7 +21,7 @@ export interface NodePrepResult extends PrepResultBase {
   packageManager: NodePackageManager;
 }
 
-export type PythonPackageManager = "pip" | "pipenv" | "poetry";
+export type PythonPackageManager = "pip" | "pipenv" | "poetry" | "uv" | "pdm";
 
 export interface PythonPrepResult extends PrepResultBase {
   language: "python";
@@ -18,14 +29,76 @@ export interface PythonPrepResult extends PrepResultBase {
   configFile: string;
 }
 
+export interface RustPrepResult extends PrepResultBase {
+  language: "rust";
+  /** toolchain pinned by rust-toolchain.toml (null when using the default toolchain) */
+  toolchain: string | null;
+  workspace: boolean;
+  /** true when `cargo build --tests` ran successfully after fetching (only with rustPrebuild) */
+  prebuilt: boolean;
+}
+
+export interface GoPrepResult extends PrepResultBase {
+  language: "go";
+  configFile: string;
+  /** `toolchain` directive from go.mod/go.work (null when absent) */
+  toolchain: string | null;
+  workspace: boolean;
+}
+
+export interface ScriptPrepResult extends PrepResultBase {
+  language: "script";
+  /** step name from the `prep` section of repo settings */
+  name: string;
+  command: string;
+  /** true when the step never ran (failed or unknown dependency, dependency cycle) */
+  skipped: boolean;
+  durationMs: number;
+}
+
 export interface UnknownLanguagePrepResult extends PrepResultBase {
   language: "unknown";
 }
 
-export type PrepResult = NodePrepResult | PythonPrepResult | UnknownLanguagePrepResult;
+export type PrepResult =
+  | NodePrepResult
+  | PythonPrepResult
+  | RustPrepResult
+  | GoPrepResult
+  | ScriptPrepResult
+  | UnknownLanguagePrepResult;
+
+/** repo settings that change how projects are installed */
+export interface PrepOptions {
+  /** compile Rust tests after fetching crates (`rustPrebuild` in repo settings) */
+  rustPrebuild: boolean;
+}
 
 export interface PrepDefinition {
   name: string;
-  shouldRun: () => Promise<boolean> | boolean;
-  run: () => Promise<PrepResult>;
+  shouldRun: (cwd: string) => Promise<boolean> | boolean;
+  run: (cwd: string, options: PrepOptions) => Promise<PrepResult>;
+  /**
+   * globs (relative to cwd) of nested projects installed together with the project at cwd.
+   * matching projects are not installed separately.
+   */
+  workspaceMembers?: (cwd: string) => string[];
+}
+
+/**
+ * repo-defined setup step (e.g. `docker compose up -d db`, `make bootstrap`)
+ */
+export interface SetupStep {
+  name: string;
+  run: string;
+  /** timeout in milliseconds (default: 10 minutes) */
+  timeout?: number | undefined;
+  env?: Record<string, string> | undefined;
+  /** working directory relative to the repo root */
+  cwd?: string | undefined;
+  /**
+   * names of steps that must succeed first.
+   * omitted means "after the previous step"; an empty array means "no dependencies".
+   */
+  needs?: string[] | undefined;
 }
@@ -0,0 +1,19 @@
+import type { Inputs } from "../../main.ts";
+
+/**
+ * test fixture: tests the network-isolated sandbox for restricted bash.
+ * registries stay reachable, everything else (and writes outside the workspace) must fail.
+ *
+ * run with: AGENT_OVERRIDE=claude pnpm play bash-isolated.ts
+ */
+export default {
+  prompt: `Use the gh_pullfrog/bash MCP tool to run each of these commands and report the result of each one:
+
+1. curl -sS -o /dev/null -w "%{http_code}" https://registry.npmjs.org/
+2. curl -sS -o /dev/null -w "%{http_code}" https://httpbin.org/json
+3. touch "$HOME/sandbox-escape.txt"
+
+The first should succeed, the second and third should fail.`,
+  bash: "restricted",
+  sandbox: "isolated",
+} satisfies Inputs;
@@ -0,0 +1,16 @@
+import type { Inputs } from "../../main.ts";
+
+/**
+ * transcript fixture - the run recorded into test/transcripts/<agent>.jsonl for test/replay.test.ts.
+ * the replay test expects exactly these tool calls and messages.
+ */
+export default {
+  prompt: `Do exactly this and nothing else:
+1. Call the select_mode tool with modeName "Build".
+2. Call the report_progress tool with this body, verbatim:
+## Summary
+
+Added a \`--dry-run\` flag to the CLI.
+3. Reply with exactly: Done. The flag is in place.`,
+  effort: "mini",
+} satisfies Inputs;
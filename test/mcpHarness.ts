@@ -0,0 +1,115 @@
+import { mkdtempSync, rmSync } from "node:fs";
+import { tmpdir } from "node:os";
+import { join } from "node:path";
+import { agents } from "../agents/index.ts";
+import type { AgentName, PayloadEvent } from "../external.ts";
+import { McpClient } from "../mcp/client.ts";
+import { type ToolContext, startMcpHttpServer } from "../mcp/server.ts";
+import { computeModes } from "../modes.ts";
+import { createOctokit } from "../utils/github.ts";
+import type { ResolvedPayload } from "../utils/payload.ts";
+import { DEFAULT_REPO_SETTINGS, type RepoSettings } from "../utils/repoSettings.ts";
+import { $ } from "../utils/shell.ts";
+import { MockGitHub } from "./mockGitHub.ts";
+
+/**
+ * runs the gh_pullfrog MCP server against a MockGitHub, with a clone of its remote as the working directory.
+ * tests drive tools through `client` and assert on `github`.
+ *
+ * the working directory and GitHub env vars are process-wide, so harnesses must not overlap.
+ */
+
+interface StartMcpHarnessParams {
+  /** a MockGitHub seeded beforehand; started here when omitted. closed with the harness either way */
+  github?: MockGitHub;
+  event?: PayloadEvent;
+  payload?: Partial<ResolvedPayload>;
+  repoSettings?: Partial<RepoSettings>;
+  agent?: AgentName;
+}
+
+export interface McpHarness {
+  github: MockGitHub;
+  client: McpClient;
+  ctx: ToolContext;
+  /** the clone of the mock remote the tools run in */
+  workdir: string;
+  /** run git in the working directory */
+  git: (...args: string[]) => string;
+  [Symbol.asyncDispose]: () => Promise<void>;
+}
+
+const harnessEnv = ["GITHUB_API_URL", "GITHUB_REPOSITORY", "GITHUB_RUN_ID", "PULLFROG_TEMP_DIR"] as const;
+
+export async function startMcpHarness(params: StartMcpHarnessParams = {}): Promise<McpHarness> {
+  const github = params.github ?? (await MockGitHub.start());
+  const tmp = mkdtempSync(join(tmpdir(), "pullfrog-mcp-harness-"));
+  const workdir = join(tmp, "repo");
+  $("git", ["clone", "--quiet", github.remotePath, workdir], { log: false });
+  const git = (...args: string[]) => $("git", args, { cwd: workdir, log: false });
+  git("config", "user.name", "pullfrog[bot]");
+  git("config", "user.email", "pullfrog[bot]@users.noreply.github.com");
+
+  const previousCwd = process.cwd();
+  const previousEnv = Object.fromEntries(harnessEnv.map((name) => [name, process.env[name]]));
+  process.env.GITHUB_API_URL = github.url;
+  process.env.GITHUB_REPOSITORY = github.fullName;
+  process.env.PULLFROG_TEMP_DIR = tmp;
+  // a run id makes comment footers look up the workflow's jobs
+  delete process.env.GITHUB_RUN_ID;
+  process.chdir(workdir);
+
+  const octokit = createOctokit("mock-installation-token");
+  const repoSettings: RepoSettings = { ...DEFAULT_REPO_SETTINGS, ...params.repoSettings };
+  const { data: repo } = await octokit.rest.repos.get({ owner: github.owner, repo: github.repo });
+  const payload: ResolvedPayload = {
+    "~pullfrog": true,
+    agent: params.agent ?? "claude",
+    prompt: "",
+    event: params.event ?? { trigger: "unknown" },
+    effort: "auto",
+    cwd: workdir,
+    web: "enabled",
+    search: "enabled",
+    write: "enabled",
+    bash: "restricted",
+    sandbox: "standard",
+    allowedHosts: [],
+    budget: {},
+    ...params.payload,
+  };
+  const ctx: ToolContext = {
+    repo: { owner: github.owner, name: github.repo, repo, repoSettings },
+    payload,
+    octokit,
+    githubInstallationToken: "mock-installation-token",
+    agent: agents[params.agent ?? "claude"],
+    modes: computeModes(),
+    toolState: { progressComment: { id: null, wasUpdated: false } },
+    runId: "",
+    jobId: undefined,
+  };
+
+  const server = await startMcpHttpServer(ctx);
+  const client = new McpClient(server.url, "pullfrog-test");
+  await client.initialize();
+
+  return {
+    github,
+    client,
+    ctx,
+    workdir,
+    git,
+    [Symbol.asyncDispose]: async () => {
+      await client.close();
+      await server[Symbol.asyncDispose]();
+      process.chdir(previousCwd);
+      for (const [name, value] of Object.entries(previousEnv)) {
+        if (value === undefined) delete process.env[name];
+        else process.env[name] = value;
+      }
+      await github.close();
+      rmSync(tmp, { recursive: true, force: true });
+    },
+  };
+}
@@ -0,0 +1,630 @@
+import { execFileSync, spawn } from "node:child_process";
+import { mkdirSync, mkdtempSync, rmSync, writeFileSync } from "node:fs";
+import { createServer, type IncomingMessage, type Server, type ServerResponse } from "node:http";
+import type { AddressInfo } from "node:net";
+import { tmpdir } from "node:os";
+import { dirname, join } from "node:path";
+import { $ } from "../utils/shell.ts";
+
+/**
+ * an in-memory stand-in for the GitHub REST and GraphQL APIs, backed by a bare git repo as the remote.
+ * covers the endpoints the MCP tools call; anything else gets a 404 so missing coverage is obvious.
+ * point octokit at it with GITHUB_API_URL and clone `remotePath` as the working copy.
+ *
+ * it also serves the remote over git's smart HTTP protocol at `<url>/<owner>/<repo>.git` and mints
+ * installation tokens for any GitHub App, so with GITHUB_SERVER_URL pointed at it too, main() can
+ * run end to end.
+ */
+
+const gitIdentity = ["-c", "user.name=Mock User", "-c", "user.email=mock@example.com"];
+
+function git(args: string[], cwd: string): string {
+  return $("git", [...gitIdentity, ...args], { cwd, log: false });
+}
+
+export interface MockUser {
+  login: string;
+}
+
+export interface MockComment {
+  id: number;
+  issueNumber: number;
+  body: string;
+  user: MockUser;
+  createdAt: string;
+  updatedAt: string;
+}
+
+export interface MockIssue {
+  number: number;
+  title: string;
+  body: string | null;
+  labels: string[];
+  state: "open" | "closed";
+  user: MockUser;
+}
+
+export interface MockPull extends MockIssue {
+  head: string;
+  base: string;
+}
+
+export interface MockReviewComment {
+  path: string;
+  line: number;
+  side?: string;
+  start_line?: number;
+  start_side?: string;
+  body: string;
+}
+
+export interface MockReview {
+  id: number;
+  pullNumber: number;
+  body: string;
+  event: string;
+  commitId: string;
+  comments: MockReviewComment[];
+  user: MockUser;
+  submittedAt: string;
+}
+
+export interface MockRequest {
+  method: string;
+  path: string;
+  body: unknown;
+}
+
+interface MockGitHubParams {
+  owner?: string;
+  repo?: string;
+  defaultBranch?: string;
+  /** files in the initial commit of the default branch */
+  files?: Record<string, string>;
+}
+
+type GraphqlResolver = (variables: Record<string, unknown>) => unknown;
+
+type RouteHandler = (match: RegExpMatchArray, body: any) => [number, unknown];
+
+class HttpError extends Error {
+  readonly status: number;
+
+  constructor(status: number, message: string) {
+    super(message);
+    this.status = status;
+  }
+}
+
+export class MockGitHub {
+  readonly owner: string;
+  readonly repo: string;
+  readonly defaultBranch: string;
+  /** the bare repo acting as `origin` */
+  readonly remotePath: string;
+  readonly bot: MockUser = { login: "pullfrog[bot]" };
+  readonly issues = new Map<number, MockIssue | MockPull>();
+  readonly comments: MockComment[] = [];
+  readonly reviews: MockReview[] = [];
+  /** every API request, in order */
+  readonly requests: MockRequest[] = [];
+  readonly protectedBranches = new Set<string>();
+  /** REST paths answered with an error status instead of their route, e.g. to simulate a 403 */
+  readonly failures: [RegExp, number][] = [];
+  /** handed out for every installation token request */
+  readonly installationToken = "ghs_mockinstallationtoken";
+  /** base URL of the REST API, e.g. http://127.0.0.1:1234 */
+  url = "";
+
+  private readonly tmp: string;
+  private readonly server: Server;
+  private readonly graphqlResolvers: [RegExp, GraphqlResolver][] = [];
+  private readonly routes: [string, RegExp, RouteHandler][];
+  private nextNumber = 1;
+  private nextId = 1000;
+
+  private constructor(params: MockGitHubParams) {
+    this.owner = params.owner ?? "pullfrog";
+    this.repo = params.repo ?? "scratch";
+    this.defaultBranch = params.defaultBranch ?? "main";
+    this.tmp = mkdtempSync(join(tmpdir(), "pullfrog-mock-github-"));
+    this.remotePath = join(this.tmp, "remote.git");
+    this.server = createServer((req, res) => {
+      this.handle(req, res).catch((error) => this.send(res, 500, { message: String(error) }));
+    });
+    this.routes = this.buildRoutes();
+    this.seedRemote(params.files ?? { "README.md": `# ${this.repo}\n` });
+  }
+
+  static async start(params: MockGitHubParams = {}): Promise<MockGitHub> {
+    const github = new MockGitHub(params);
+    await new Promise<void>((resolve) => github.server.listen(0, "127.0.0.1", resolve));
+    const { port } = github.server.address() as AddressInfo;
+    github.url = `http://127.0.0.1:${port}`;
+    return github;
+  }
+
+  get fullName(): string {
+    return `${this.owner}/${this.repo}`;
+  }
+
+  createIssue(params: { title: string; body?: string; labels?: string[] }): MockIssue {
+    const issue: MockIssue = {
+      number: this.nextNumber++,
+      title: params.title,
+      body: params.body ?? null,
+      labels: params.labels ?? [],
+      state: "open",
+      user: { login: "octocat" },
+    };
+    this.issues.set(issue.number, issue);
+    return issue;
+  }
+
+  createPull(params: { title: string; head: string; base?: string; body?: string; user?: MockUser }): MockPull {
+    if (!this.branchSha(params.head)) {
+      throw new Error(`head branch ${params.head} does not exist on the remote`);
+    }
+    const pull: MockPull = {
+      number: this.nextNumber++,
+      title: params.title,
+      body: params.body ?? null,
+      labels: [],
+      state: "open",
+      user: params.user ?? { login: "octocat" },
+      head: params.head,
+      base: params.base ?? this.defaultBranch,
+    };
+    this.issues.set(pull.number, pull);
+    this.syncPullRef(pull);
+    return pull;
+  }
+
+  /** commit files onto a branch of the remote, starting it from `base` if it doesn't exist */
+  pushBranch(branch: string, files: Record<string, string>, params: { base?: string; message?: string } = {}): string {
+    const work = join(this.tmp, `push-${this.nextId++}`);
+    git(["clone", "--quiet", this.remotePath, work], this.tmp);
+    const start = this.branchSha(branch) ? branch : (params.base ?? this.defaultBranch);
+    git(["checkout", "--quiet", "-B", branch, `origin/${start}`], work);
+    this.writeFiles(work, files);
+    git(["add", "-A"], work);
+    git(["commit", "--quiet", "-m", params.message ?? `update ${branch}`], work);
+    git(["push", "--quiet", "origin", branch], work);
+    rmSync(work, { recursive: true, force: true });
+    return this.branchSha(branch)!;
+  }
+
+  /** sha of a branch on the remote, or null when it hasn't been pushed */
+  branchSha(branch: string): string | null {
+    try {
+      return git(["rev-parse", "--verify", "--quiet", `refs/heads/${branch}`], this.remotePath);
+    } catch {
+      return null;
+    }
+  }
+
+  /** contents of a file on a branch of the remote, trimmed */
+  readFile(branch: string, path: string): string {
+    return git(["show", `refs/heads/${branch}:${path}`], this.remotePath);
+  }
+
+  /** untrimmed contents of a file on a branch of the remote, or null when it doesn't exist there */
+  fileContent(branch: string, path: string): string | null {
+    try {
+      return execFileSync("git", ["show", `refs/heads/${branch}:${path}`], { cwd: this.remotePath, encoding: "utf-8", stdio: "pipe" });
+    } catch {
+      return null;
+    }
+  }
+
+  commentsOn(issueNumber: number): MockComment[] {
+    return this.comments.filter((comment) => comment.issueNumber === issueNumber);
+  }
+
+  /** answer GraphQL queries matching `pattern`; unmatched queries get an error response */
+  onGraphql(pattern: RegExp, resolver: GraphqlResolver): void {
+    this.graphqlResolvers.push([pattern, resolver]);
+  }
+
+  async close(): Promise<void> {
+    // octokit keeps connections alive, which would hold close() open
+    this.server.closeAllConnections();
+    await new Promise<void>((resolve) => this.server.close(() => resolve()));
+    rmSync(this.tmp, { recursive: true, force: true });
+  }
+
+  private seedRemote(files: Record<string, string>): void {
+    git(["init", "--quiet", "--bare", `--initial-branch=${this.defaultBranch}`, this.remotePath], this.tmp);
+    const seed = join(this.tmp, "seed");
+    git(["init", "--quiet", `--initial-branch=${this.defaultBranch}`, seed], this.tmp);
+    this.writeFiles(seed, files);
+    git(["add", "-A"], seed);
+    git(["commit", "--quiet", "-m", "initial commit"], seed);
+    git(["push", "--quiet", this.remotePath, this.defaultBranch], seed);
+    rmSync(seed, { recursive: true, force: true });
+  }
+
+  private writeFiles(dir: string, files: Record<string, string>): void {
+    for (const [path, content] of Object.entries(files)) {
+      mkdirSync(dirname(join(dir, path)), { recursive: true });
+      writeFileSync(join(dir, path), content);
+    }
+  }
+
+  /** GitHub keeps refs/pull/N/head pointing at the PR's head, which checkout_pr fetches */
+  private syncPullRef(pull: MockPull): string {
+    const sha = this.branchSha(pull.head);
+    if (!sha) throw new HttpError(422, `head branch ${pull.head} no longer exists`);
+    git(["update-ref", `refs/pull/${pull.number}/head`, sha], this.remotePath);
+    return sha;
+  }
+
+  private htmlUrl(path: string): string {
+    return `https://github.com/${this.fullName}/${path}`;
+  }
+
+  private repoJson() {
+    return {
+      id: 1,
+      node_id: "R_1",
+      name: this.repo,
+      full_name: this.fullName,
+      owner: { login: this.owner },
+      private: false,
+      default_branch: this.defaultBranch,
+      html_url: this.htmlUrl("").replace(/\/$/, ""),
+    };
+  }
+
+  private issueJson(issue: MockIssue) {
+    return {
+      id: issue.number,
+      number: issue.number,
+      title: issue.title,
+      body: issue.body,
+      state: issue.state,
+      user: issue.user,
+      labels: issue.labels.map((name) => ({ name })),
+      html_url: this.htmlUrl(`issues/${issue.number}`),
+      ...("head" in issue && { pull_request: { url: this.htmlUrl(`pull/${issue.number}`) } }),
+    };
+  }
+
+  private pullJson(pull: MockPull) {
+    const repo = this.repoJson();
+    return {
+      id: pull.number,
+      number: pull.number,
+      title: pull.title,
+      body: pull.body,
+      state: pull.state,
+      draft: false,
+      user: pull.user,
+      labels: pull.labels.map((name) => ({ name })),
+      html_url: this.htmlUrl(`pull/${pull.number}`),
+      maintainer_can_modify: false,
+      head: { ref: pull.head, sha: this.syncPullRef(pull), repo },
+      base: { ref: pull.base, sha: this.branchSha(pull.base), repo },
+    };
+  }
+
+  private commentJson(comment: MockComment) {
+    return {
+      id: comment.id,
+      body: comment.body,
+      user: comment.user,
+      created_at: comment.createdAt,
+      updated_at: comment.updatedAt,
+      html_url: this.htmlUrl(`issues/${comment.issueNumber}#issuecomment-${comment.id}`),
+    };
+  }
+
+  private reviewJson(review: MockReview) {
+    return {
+      id: review.id,
+      node_id: `PRR_${review.id}`,
+      body: review.body,
+      state: review.event === "APPROVE" ? "APPROVED" : review.event === "REQUEST_CHANGES" ? "CHANGES_REQUESTED" : "COMMENTED",
+      commit_id: review.commitId,
+      user: review.user,
+      submitted_at: review.submittedAt,
+      html_url: this.htmlUrl(`pull/${review.pullNumber}#pullrequestreview-${review.id}`),
+    };
+  }
+
+  private pullFiles(pull: MockPull) {
+    const range = `refs/heads/${pull.base}...refs/heads/${pull.head}`;
+    const statuses: Record<string, string> = { A: "added", M: "modified", D: "removed" };
+    const numstat = git(["diff", "--numstat", "--no-renames", range], this.remotePath);
+    const nameStatus = git(["diff", "--name-status", "--no-renames", range], this.remotePath);
+    const status = new Map(
+      nameStatus
+        .split("\n")
+        .filter(Boolean)
+        .map((line) => {
+          const [code, path] = line.split("\t");
+          return [path!, statuses[code!] ?? "modified"] as const;
+        })
+    );
+    return numstat
+      .split("\n")
+      .filter(Boolean)
+      .map((line) => {
+        const [additions, deletions, filename] = line.split("\t") as [string, string, string];
+        const diff = git(["diff", "--no-renames", range, "--", filename], this.remotePath);
+        // GitHub's patch starts at the first hunk header
+        const hunkStart = diff.indexOf("@@");
+        return {
+          filename,
+          status: status.get(filename) ?? "modified",
+          additions: Number(additions),
+          deletions: Number(deletions),
+          changes: Number(additions) + Number(deletions),
+          ...(hunkStart >= 0 && { patch: diff.slice(hunkStart) }),
+        };
+      });
+  }
+
+  private getIssue(number: string): MockIssue | MockPull {
+    const issue = this.issues.get(Number(number));
+    if (!issue) throw new HttpError(404, "Not Found");
+    return issue;
+  }
+
+  private getPull(number: string): MockPull {
+    const issue = this.getIssue(number);
+    if (!("head" in issue)) throw new HttpError(404, "Not Found");
+    return issue;
+  }
+
+  private getComment(id: string): MockComment {
+    const comment = this.comments.find((c) => c.id === Number(id));
+    if (!comment) throw new HttpError(404, "Not Found");
+    return comment;
+  }
+
+  private buildRoutes(): [string, RegExp, RouteHandler][] {
+    const repo = "/repos/[^/]+/[^/]+";
+    const now = () => new Date().toISOString();
+    return [
+      ["GET", new RegExp(`^${repo}$`), () => [200, this.repoJson()]],
+      [
+        "GET",
+        new RegExp(`^${repo}/branches/(.+)$`),
+        ([, branch]) => {
+          const sha = this.branchSha(branch!);
+          if (!sha) throw new HttpError(404, "Branch not found");
+          return [200, { name: branch, commit: { sha }, protected: this.protectedBranches.has(branch!) }];
+        },
+      ],
+      [
+        "GET",
+        new RegExp(`^${repo}/contents/(.+)$`),
+        // always the default branch, which is all the action reads
+        ([, path]) => {
+          const content = this.fileContent(this.defaultBranch, decodeURIComponent(path!));
+          if (content === null) throw new HttpError(404, "Not Found");
+          return [200, { type: "file", path, encoding: "base64", content: Buffer.from(content).toString("base64") }];
+        },
+      ],
+      ["GET", new RegExp(`^${repo}/issues/(\\d+)$`), ([, n]) => [200, this.issueJson(this.getIssue(n!))]],
+      [
+        "POST",
+        new RegExp(`^${repo}/issues$`),
+        (_, body) => [201, this.issueJson(this.createIssue({ title: body.title, body: body.body, labels: body.labels }))],
+      ],
+      [
+        "GET",
+        new RegExp(`^${repo}/issues/(\\d+)/comments$`),
+        ([, n]) => [200, this.commentsOn(this.getIssue(n!).number).map((c) => this.commentJson(c))],
+      ],
+      [
+        "POST",
+        new RegExp(`^${repo}/issues/(\\d+)/comments$`),
+        ([, n], body) => {
+          const time = now();
+          const comment: MockComment = {
+            id: this.nextId++,
+            issueNumber: this.getIssue(n!).number,
+            body: body.body,
+            user: this.bot,
+            createdAt: time,
+            updatedAt: time,
+          };
+          this.comments.push(comment);
+          return [201, this.commentJson(comment)];
+        },
+      ],
+      ["GET", new RegExp(`^${repo}/issues/comments/(\\d+)$`), ([, id]) => [200, this.commentJson(this.getComment(id!))]],
+      [
+        "PATCH",
+        new RegExp(`^${repo}/issues/comments/(\\d+)$`),
+        ([, id], body) => {
+          const comment = this.getComment(id!);
+          comment.body = body.body;
+          comment.updatedAt = now();
+          return [200, this.commentJson(comment)];
+        },
+      ],
+      [
+        "DELETE",
+        new RegExp(`^${repo}/issues/comments/(\\d+)$`),
+        ([, id]) => {
+          this.comments.splice(this.comments.indexOf(this.getComment(id!)), 1);
+          return [204, null];
+        },
+      ],
+      [
+        "GET",
+        new RegExp(`^${repo}/issues/(\\d+)/labels$`),
+        ([, n]) => [200, this.getIssue(n!).labels.map((name) => ({ name }))],
+      ],
+      [
+        "POST",
+        new RegExp(`^${repo}/issues/(\\d+)/labels$`),
+        ([, n], body) => {
+          const issue = this.getIssue(n!);
+          issue.labels = [...new Set([...issue.labels, ...(body.labels as string[])])];
+          return [200, issue.labels.map((name) => ({ name }))];
+        },
+      ],
+      [
+        "POST",
+        new RegExp(`^${repo}/pulls$`),
+        (_, body) => {
+          if (!this.branchSha(body.head)) throw new HttpError(422, `Validation Failed: head ${body.head} not found`);
+          const pull = this.createPull({ title: body.title, head: body.head, base: body.base, body: body.body, user: this.bot });
+          return [201, this.pullJson(pull)];
+        },
+      ],
+      ["GET", new RegExp(`^${repo}/pulls/(\\d+)$`), ([, n]) => [200, this.pullJson(this.getPull(n!))]],
+      ["GET", new RegExp(`^${repo}/pulls/(\\d+)/files$`), ([, n]) => [200, this.pullFiles(this.getPull(n!))]],
+      [
+        "GET",
+        new RegExp(`^${repo}/pulls/(\\d+)/reviews$`),
+        ([, n]) => [
+          200,
+          this.reviews.filter((r) => r.pullNumber === this.getPull(n!).number).map((r) => this.reviewJson(r)),
+        ],
+      ],
+      [
+        "POST",
+        new RegExp(`^${repo}/pulls/(\\d+)/reviews$`),
+        ([, n], body) => {
+          const pull = this.getPull(n!);
+          const review: MockReview = {
+            id: this.nextId++,
+            pullNumber: pull.number,
+            body: body.body ?? "",
+            event: body.event ?? "PENDING",
+            commitId: body.commit_id ?? this.syncPullRef(pull),
+            comments: body.comments ?? [],
+            user: this.bot,
+            submittedAt: now(),
+          };
+          this.reviews.push(review);
+          return [200, this.reviewJson(review)];
+        },
+      ],
+      [
+        "PUT",
+        new RegExp(`^${repo}/pulls/(\\d+)/reviews/(\\d+)$`),
+        ([, , id], body) => {
+          const review = this.reviews.find((r) => r.id === Number(id));
+          if (!review) throw new HttpError(404, "Not Found");
+          review.body = body.body;
+          return [200, this.reviewJson(review)];
+        },
+      ],
+      [
+        "GET",
+        /^\/app\/installations$/,
+        () => [200, [{ id: 1, account: { login: this.owner, type: "Organization" } }]],
+      ],
+      [
+        "POST",
+        /^\/app\/installations\/\d+\/access_tokens$/,
+        () => [201, { token: this.installationToken, expires_at: new Date(Date.now() + 3_600_000).toISOString() }],
+      ],
+      [
+        "GET",
+        /^\/installation\/repositories$/,
+        () => [200, { total_count: 1, repositories: [this.repoJson()] }],
+      ],
+      ["DELETE", /^\/installation\/token$/, () => [204, null]],
+      ["GET", new RegExp(`^${repo}/actions/runs/(\\d+)/jobs$`), () => [200, { total_count: 0, jobs: [] }]],
+      [
+        "POST",
+        /^\/graphql$/,
+        (_, body) => {
+          const resolver = this.graphqlResolvers.find(([pattern]) => pattern.test(body.query));
+          if (!resolver) return [200, { errors: [{ message: "the mock GitHub API has no resolver for this query" }] }];
+          return [200, { data: resolver[1](body.variables ?? {}) }];
+        },
+      ],
+    ];
+  }
+
+  private async handle(req: IncomingMessage, res: ServerResponse): Promise<void> {
+    const gitPath = new URL(req.url ?? "/", this.url).pathname.match(/^\/([^/]+)\/([^/]+)\.git(\/.*)$/);
+    if (gitPath && gitPath[1] === this.owner && gitPath[2] === this.repo) {
+      await this.serveGit(req, res, gitPath[3]!);
+      return;
+    }
+
+    const chunks: Buffer[] = [];
+    for await (const chunk of req) chunks.push(chunk as Buffer);
+    const text = Buffer.concat(chunks).toString("utf-8");
+    const body = text ? JSON.parse(text) : undefined;
+    const url = new URL(req.url ?? "/", this.url);
+    const method = req.method ?? "GET";
+    this.requests.push({ method, path: url.pathname, body });
+
+    const failure = this.failures.find(([pattern]) => pattern.test(url.pathname));
+    if (failure) {
+      this.send(res, failure[1], { message: "simulated failure" });
+      return;
+    }
+
+    for (const [routeMethod, pattern, handler] of this.routes) {
+      if (routeMethod !== method) continue;
+      const match = url.pathname.match(pattern);
+      if (!match) continue;
+      try {
+        const [status, data] = handler(match, body);
+        this.send(res, status, data);
+      } catch (error) {
+        if (!(error instanceof HttpError)) throw error;
+        this.send(res, error.status, { message: error.message });
+      }
+      return;
+    }
+    this.send(res, 404, { message: "Not Found" });
+  }
+
+  /** run `git http-backend` as a CGI script against the bare remote */
+  private async serveGit(req: IncomingMessage, res: ServerResponse, path: string): Promise<void> {
+    const url = new URL(req.url ?? "/", this.url);
+    const header = (name: string) => (req.headers[name] as string | undefined) ?? "";
+    const backend = spawn("git", ["http-backend"], {
+      env: {
+        ...process.env,
+        GIT_PROJECT_ROOT: this.tmp,
+        GIT_HTTP_EXPORT_ALL: "1",
+        // http-backend only accepts pushes from an authenticated user
+        REMOTE_USER: "x-access-token",
+        REQUEST_METHOD: req.method ?? "GET",
+        PATH_INFO: `/remote.git${path}`,
+        QUERY_STRING: url.search.slice(1),
+        CONTENT_TYPE: header("content-type"),
+        HTTP_CONTENT_ENCODING: header("content-encoding"),
+        GIT_PROTOCOL: header("git-protocol"),
+        ...(req.headers["content-length"] && { CONTENT_LENGTH: header("content-length") }),
+      },
+    });
+    req.pipe(backend.stdin);
+    const chunks: Buffer[] = [];
+    for await (const chunk of backend.stdout) chunks.push(chunk as Buffer);
+    await new Promise((resolve) => backend.once("close", resolve));
+
+    // CGI output: headers, a blank line, then the body
+    const output = Buffer.concat(chunks);
+    const headerEnd = output.indexOf("\r\n\r\n");
+    const headers = output
+      .subarray(0, headerEnd)
+      .toString("utf-8")
+      .split("\r\n")
+      .map((line) => [line.slice(0, line.indexOf(":")), line.slice(line.indexOf(":") + 1).trim()] as const);
+    const status = Number(headers.find(([name]) => name.toLowerCase() === "status")?.[1].split(" ")[0] ?? 200);
+    res.writeHead(status, Object.fromEntries(headers.filter(([name]) => name.toLowerCase() !== "status")));
+    res.end(output.subarray(headerEnd + 4));
+  }
+
+  private send(res: ServerResponse, status: number, data: unknown): void {
+    if (status === 204) {
+      res.writeHead(204).end();
+      return;
+    }
+    res.writeHead(status, { "content-type": "application/json; charset=utf-8" }).end(JSON.stringify(data));
+  }
+}
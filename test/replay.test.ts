@@ -0,0 +1,40 @@
+import { join } from 'node:path';
+import type { AgentName } from '../external.ts';
+import { readRawTranscript, replayTranscript, transcriptsDir } from './replay.ts';
+
+const replay = (agent: AgentName) => replayTranscript(agent, readRawTranscript(join(transcriptsDir, `${agent}.jsonl`)));
+
+const progressBody = '## Summary\n\nAdded a `--dry-run` flag to the CLI.';
+
+describe('transcript replay', () => {
+  it.each(['claude', 'gemini'] as const)('%s: finds the MCP calls and job summary', async (agent) => {
+    const result = await replay(agent);
+    expect(result.mcpCalls).toEqual([
+      { tool: 'select_mode', input: { modeName: 'Build' } },
+      { tool: 'report_progress', input: { body: progressBody } },
+    ]);
+    expect(result.summary).toBe(progressBody);
+    const messages = result.events.flatMap((event) => (event.type === 'message' ? [event.text] : []));
+    expect(messages.at(-1)).toBe('Done. The flag is in place.');
+    expect(result.logs).toContainEqual({ level: 'box', text: 'Done. The flag is in place.' });
+  });
+
+  it('claude: takes the run totals and cost from the result message', async () => {
+    const result = await replay('claude');
+    expect(result.usage).toMatchObject({
+      inputTokens: 15650,
+      outputTokens: 140,
+      cacheReadTokens: 13500,
+      cacheWriteTokens: 500,
+      costUsd: 0.0213,
+    });
+    expect(result.events).toContainEqual({ type: 'tool_result', id: 'toolu_02', output: 'File does not exist.', isError: true });
+    expect(result.logs).toContainEqual({ level: 'warning', text: 'Tool error: File does not exist.' });
+  });
+
+  it('gemini: joins streamed deltas and reports failed tools', async () => {
+    const result = await replay('gemini');
+    expect(result.usage).toMatchObject({ inputTokens: 15000, outputTokens: 800 });
+    expect(result.logs).toContainEqual({ level: 'warning', text: 'Tool call failed: 1 test failed' });
+  });
+});
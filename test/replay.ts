@@ -0,0 +1,131 @@
+import { readFileSync } from "node:fs";
+import { dirname, join } from "node:path";
+import { fileURLToPath } from "node:url";
+import { type AgentEvent, AgentEventStream } from "../agents/events.ts";
+import { type Agent, agents } from "../agents/index.ts";
+import { type AgentName, ghPullfrogMcpName } from "../external.ts";
+import { type AgentUsage, RunBudgetTracker } from "../utils/budget.ts";
+import { log } from "../utils/cli.ts";
+
+/**
+ * replays recorded agent output through an adapter's output handler, offline.
+ *
+ * record a transcript by running an agent with PULLFROG_RAW_TRANSCRIPT set to an absolute path,
+ * e.g. `PULLFROG_RAW_TRANSCRIPT=$PWD/test/transcripts/claude.jsonl node play.ts --local transcript.ts`.
+ * each line is one SDK message or parsed line of the CLI's JSON output.
+ * the committed transcripts are still hand-written stand-ins; see test/transcripts/README.md.
+ */
+
+const __dirname = dirname(fileURLToPath(import.meta.url));
+export const transcriptsDir = join(__dirname, "transcripts");
+
+export interface ReplayLog {
+  level: "info" | "warning" | "error" | "box" | "table";
+  text: string;
+}
+
+export interface McpCall {
+  /** tool name without the server prefix, e.g. "report_progress" */
+  tool: string;
+  input: unknown;
+}
+
+export interface ReplayResult {
+  events: AgentEvent[];
+  /** calls to gh_pullfrog tools, whatever naming scheme the agent uses for MCP tools */
+  mcpCalls: McpCall[];
+  /** the last report_progress body, which main() writes to the job summary */
+  summary: string | undefined;
+  usage: AgentUsage;
+  logs: ReplayLog[];
+}
+
+export function readRawTranscript(path: string): unknown[] {
+  return readFileSync(path, "utf-8")
+    .split("\n")
+    .filter((line) => line.trim())
+    .map((line) => JSON.parse(line));
+}
+
+class CapturingEventStream extends AgentEventStream {
+  readonly captured: AgentEvent[] = [];
+
+  emit(event: AgentEvent): void {
+    this.captured.push(event);
+    super.emit(event);
+  }
+}
+
+function formatLogArgs(args: unknown[]): string {
+  return args.map((arg) => (typeof arg === "string" ? arg : JSON.stringify(arg))).join(" ");
+}
+
+/**
+ * strip the server prefix each agent puts on MCP tool names:
+ * claude `mcp__gh_pullfrog__x`, gemini `gh_pullfrog__x`.
+ */
+function toMcpCall(event: Extract<AgentEvent, { type: "tool_call" }>): McpCall | null {
+  for (const prefix of [`mcp__${ghPullfrogMcpName}__`, `${ghPullfrogMcpName}__`]) {
+    if (event.tool.startsWith(prefix)) {
+      return { tool: event.tool.slice(prefix.length), input: event.input };
+    }
+  }
+  return null;
+}
+
+/**
+ * feed recorded output records to an agent's output handler and collect what it produced.
+ * must run inside a vitest test: log output is captured with spies, which are restored afterwards.
+ */
+export async function replayTranscript(agentName: AgentName, records: unknown[]): Promise<ReplayResult> {
+  const logs: ReplayLog[] = [];
+  const capture = (level: ReplayLog["level"]) => (...args: unknown[]) => {
+    logs.push({ level, text: formatLogArgs(args) });
+  };
+  const spies = [
+    vi.spyOn(log, "info").mockImplementation(capture("info")),
+    vi.spyOn(log, "success").mockImplementation(capture("info")),
+    vi.spyOn(log, "warning").mockImplementation(capture("warning")),
+    vi.spyOn(log, "error").mockImplementation(capture("error")),
+    // the title is the only option boxes are given, so only the text is kept
+    vi.spyOn(log, "box").mockImplementation((text) => {
+      logs.push({ level: "box", text });
+    }),
+    vi.spyOn(log, "table").mockImplementation(capture("table")),
+    vi.spyOn(log, "debug").mockImplementation(() => {}),
+    vi.spyOn(log, "startGroup").mockImplementation(() => {}),
+    vi.spyOn(log, "endGroup").mockImplementation(() => {}),
+  ];
+
+  const agent: Agent = agents[agentName];
+  const createOutputHandler = agent.createOutputHandler;
+  if (!createOutputHandler) {
+    throw new Error(`${agentName} parses its output inline and can't be replayed`);
+  }
+  const events = new CapturingEventStream({ title: agent.displayName });
+  const budget = new RunBudgetTracker({});
+  try {
+    const handleRecord = createOutputHandler({ events, budget });
+    for (const record of records) {
+      await handleRecord(record);
+    }
+  } finally {
+    budget.dispose();
+    for (const spy of spies) spy.mockRestore();
+  }
+
+  const mcpCalls = events.captured.flatMap((event) => {
+    if (event.type !== "tool_call") return [];
+    const call = toMcpCall(event);
+    return call ? [call] : [];
+  });
+  const progress = mcpCalls.filter((call) => call.tool === "report_progress").at(-1);
+
+  return {
+    events: events.captured,
+    mcpCalls,
+    summary: (progress?.input as { body?: string } | undefined)?.body,
+    usage: budget.usage(),
+    logs,
+  };
+}
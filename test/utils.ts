@@ -56,7 +56,10 @@ function startSpinner(message: string): Spinner {
 config({ path: join(actionDir, ".env") });
 config({ path: join(actionDir, "..", ".env") });
 
-export const agents = Object.keys(agentsManifest) as (keyof typeof agentsManifest)[];
+// the scripted agent has no model to test
+export const agents = (Object.keys(agentsManifest) as (keyof typeof agentsManifest)[]).filter(
+  (agent) => agent !== "scripted"
+);
 
 export interface AgentResult {
   agent: string;
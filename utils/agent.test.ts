@@ -0,0 +1,42 @@
+import { agents } from '../agents/index.ts';
+import { resolveAgent } from './agent.ts';
+import { validateApiKey } from './apiKeys.ts';
+import * as cli from './cli.ts';
+import type { ResolvedPayload } from './payload.ts';
+import type { RepoSettings } from './repoSettings.ts';
+
+describe('resolveAgent', () => {
+  const resolve = (agent: string | null, repoSettings: Partial<RepoSettings> = {}) =>
+    resolveAgent({ payload: { agent } as ResolvedPayload, repoSettings: repoSettings as RepoSettings });
+
+  beforeEach(() => {
+    vi.spyOn(cli.log, 'info').mockImplementation(() => {});
+    vi.spyOn(cli.log, 'warning').mockImplementation(() => {});
+    vi.spyOn(cli.log, 'debug').mockImplementation(() => {});
+    for (const key of Object.keys(process.env)) {
+      if (key.includes('API_KEY')) vi.stubEnv(key, '');
+    }
+    vi.stubEnv('AGENT_OVERRIDE', undefined);
+    vi.stubEnv('PULLFROG_AGENT_SCRIPT', '/tmp/script.yml');
+  });
+
+  afterEach(() => {
+    vi.restoreAllMocks();
+    vi.unstubAllEnvs();
+  });
+
+  it('never picks the scripted agent on its own', () => {
+    expect(() => resolve(null)).toThrow('no agents available');
+    vi.stubEnv('OPENAI_API_KEY', 'sk-test');
+    expect(resolve(null, { defaultAgent: 'scripted' }).name).toBe('codex');
+  });
+
+  it('runs the scripted agent when it is named, without an API key', () => {
+    const agent = resolve('scripted');
+    expect(agent.name).toBe('scripted');
+    expect(() => validateApiKey({ agent, owner: 'pullfrog', name: 'test-repo' })).not.toThrow();
+    expect(() => validateApiKey({ agent: agents.codex, owner: 'pullfrog', name: 'test-repo' })).toThrow(
+      'API key was not provided'
+    );
+  });
+});
@@ -5,9 +5,11 @@ import type { ResolvedPayload } from "./payload.ts";
 import type { RepoSettings } from "./repoSettings.ts";
 
 /**
- * Check if an agent has API keys available (from process.env)
+ * Check if an agent has API keys available (from process.env).
+ * keyless agents never do, so they're only run when selected explicitly.
  */
 function agentHasApiKeys(agent: Agent): boolean {
+  if (agent.keyless) return false;
   // empty apiKeyNames means agent accepts any *API_KEY* env var
   if (agent.apiKeyNames.length === 0) {
     return Object.keys(process.env).some((key) => key.includes("API_KEY") && process.env[key]);
@@ -1,13 +1,14 @@
 import type { Agent } from "../agents/index.ts";
+import { gitHubWebUrl } from "./githubHost.ts";
+import { getPullfrogApiUrl } from "./selfHosted.ts";
 
 /**
  * Build a helpful error message for missing API key with links to repo settings
  */
 function buildMissingApiKeyError(params: { agent: Agent; owner: string; name: string }): string {
-  const apiUrl = process.env.API_URL || "https://pullfrog.com";
-  const settingsUrl = `${apiUrl}/console/${params.owner}/${params.name}`;
+  const apiUrl = getPullfrogApiUrl();
 
-  const githubRepoUrl = `https://github.com/${params.owner}/${params.name}`;
+  const githubRepoUrl = gitHubWebUrl(`${params.owner}/${params.name}`);
   const githubSecretsUrl = `${githubRepoUrl}/settings/secrets/actions`;
 
   let secretNameList: string;
@@ -30,7 +31,11 @@ To fix this, add the required secret to your GitHub repository:
 4. Set the value to your API key
 5. Click "Add secret"
 
-Alternatively, configure Pullfrog to use a different agent at ${settingsUrl}`;
+Alternatively, configure Pullfrog to use a different agent ${
+    apiUrl
+      ? `at ${apiUrl}/console/${params.owner}/${params.name}`
+      : "with `defaultAgent` in .pullfrog.yml or the `agent` input"
+  }`;
 }
 
 function collectApiKeys(agent: Agent): Record<string, string> {
@@ -57,6 +62,7 @@ function collectApiKeys(agent: Agent): Record<string, string> {
 }
 
 export function validateApiKey(params: { agent: Agent; owner: string; name: string }): void {
+  if (params.agent.keyless) return;
   const apiKeys = collectApiKeys(params.agent);
 
   if (Object.keys(apiKeys).length === 0) {
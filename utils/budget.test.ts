@@ -0,0 +1,75 @@
+import { RunBudgetTracker, warnIfCostLimitUnenforced } from './budget.ts';
+import * as cli from './cli.ts';
+
+describe('RunBudgetTracker', () => {
+  beforeEach(() => {
+    vi.spyOn(cli.log, 'warning').mockImplementation(() => {});
+    vi.useFakeTimers();
+  });
+
+  afterEach(() => {
+    vi.restoreAllMocks();
+    vi.useRealTimers();
+  });
+
+  it('sums usage without aborting under budget', () => {
+    const budget = new RunBudgetTracker({ maxTokens: 1000, maxUsd: 1 });
+    budget.add({ inputTokens: 300, outputTokens: 100, costUsd: 0.2 });
+    budget.add({ inputTokens: 300, outputTokens: 100, cacheReadTokens: 250 });
+    vi.advanceTimersByTime(1500);
+    expect(budget.usage()).toEqual({ inputTokens: 600, outputTokens: 200, cacheReadTokens: 250, cacheWriteTokens: 0, costUsd: 0.2, durationMs: 1500 });
+    expect(budget.signal.aborted).toBe(false);
+  });
+
+  it('aborts once tokens exceed the limit', () => {
+    const budget = new RunBudgetTracker({ maxTokens: 1000 });
+    budget.add({ inputTokens: 900, outputTokens: 200 });
+    expect(budget.signal.aborted).toBe(true);
+    expect(budget.exceededReason).toBe('the run used 1100 tokens, over its 1000 token limit');
+  });
+
+  it('aborts on cost reported as run totals', () => {
+    const budget = new RunBudgetTracker({ maxUsd: 0.5 });
+    budget.set({ inputTokens: 10, costUsd: 0.75 });
+    expect(budget.exceededReason).toBe('the run cost $0.75, over its $0.5 limit');
+  });
+
+  it('ignores cost limits for agents that report no cost', () => {
+    const budget = new RunBudgetTracker({ maxUsd: 0.5 });
+    budget.add({ inputTokens: 1_000_000 });
+    expect(budget.signal.aborted).toBe(false);
+  });
+
+  it('aborts when the time limit passes and keeps the first reason', () => {
+    const budget = new RunBudgetTracker({ maxMinutes: 2, maxTokens: 10 });
+    vi.advanceTimersByTime(2 * 60_000);
+    expect(budget.exceededReason).toBe('the run took longer than its 2 minute limit');
+    budget.add({ outputTokens: 50 });
+    expect(budget.exceededReason).toBe('the run took longer than its 2 minute limit');
+  });
+
+  it('stops the timer on dispose', () => {
+    const budget = new RunBudgetTracker({ maxMinutes: 1 });
+    budget.dispose();
+    vi.advanceTimersByTime(60_000);
+    expect(budget.signal.aborted).toBe(false);
+  });
+});
+
+describe('warnIfCostLimitUnenforced', () => {
+  afterEach(() => {
+    vi.restoreAllMocks();
+  });
+
+  it('warns when max_usd is set for an agent that does not report cost', () => {
+    const warning = vi.spyOn(cli.log, 'warning').mockImplementation(() => {});
+    const codex = { displayName: 'Codex CLI', reportsCost: false };
+    const claude = { displayName: 'Claude Code', reportsCost: true };
+
+    expect(warnIfCostLimitUnenforced({ maxUsd: 2 }, codex)).toBe(true);
+    expect(warning).toHaveBeenCalledWith(expect.stringContaining("Codex CLI doesn't report cost"));
+    expect(warnIfCostLimitUnenforced({ maxUsd: 2 }, claude)).toBe(false);
+    expect(warnIfCostLimitUnenforced({ maxTokens: 1000 }, codex)).toBe(false);
+    expect(warning).toHaveBeenCalledTimes(1);
+  });
+});
@@ -0,0 +1,124 @@
+import { log } from "./cli.ts";
+
+/**
+ * token and cost usage of an agent run, normalized across agents.
+ * returned in AgentResult.metadata.usage.
+ */
+export interface AgentUsage {
+  /** all input tokens, including cache reads and writes */
+  inputTokens: number;
+  outputTokens: number;
+  cacheReadTokens: number;
+  cacheWriteTokens: number;
+  /** null when the agent doesn't report cost */
+  costUsd: number | null;
+  durationMs: number;
+}
+
+/**
+ * per-run limits. a run that crosses one is aborted. unset limits are not enforced.
+ */
+export interface RunBudget {
+  maxUsd?: number | undefined;
+  /** input plus output tokens */
+  maxTokens?: number | undefined;
+  maxMinutes?: number | undefined;
+}
+
+/**
+ * warns when max_usd is set for an agent that never reports cost, since the limit can't be enforced.
+ * returns whether it warned.
+ */
+export function warnIfCostLimitUnenforced(
+  budget: RunBudget,
+  agent: { displayName: string; reportsCost: boolean }
+): boolean {
+  if (budget.maxUsd === undefined || agent.reportsCost) return false;
+  log.warning(
+    `» max_usd is set to $${budget.maxUsd}, but ${agent.displayName} doesn't report cost, so it won't be enforced. use max_tokens to cap this agent instead.`
+  );
+  return true;
+}
+
+type UsageCounts = Partial<Omit<AgentUsage, "durationMs">>;
+
+/**
+ * Tracks usage reported by an agent against the run budget.
+ * When a limit is crossed the signal is aborted; agents pass it to their SDK or child process.
+ */
+export class RunBudgetTracker {
+  readonly budget: RunBudget;
+  readonly signal: AbortSignal;
+  /** why the run was cut off, once a limit is crossed */
+  exceededReason: string | null = null;
+
+  private readonly controller = new AbortController();
+  private readonly startTime = Date.now();
+  private readonly timeoutId: NodeJS.Timeout | undefined;
+  private counts: Omit<AgentUsage, "durationMs"> = {
+    inputTokens: 0,
+    outputTokens: 0,
+    cacheReadTokens: 0,
+    cacheWriteTokens: 0,
+    costUsd: null,
+  };
+
+  constructor(budget: RunBudget) {
+    this.budget = budget;
+    this.signal = this.controller.signal;
+    if (budget.maxMinutes !== undefined) {
+      const minutes = budget.maxMinutes;
+      this.timeoutId = setTimeout(
+        () => this.abort(`the run took longer than its ${minutes} minute limit`),
+        minutes * 60_000
+      );
+      this.timeoutId.unref();
+    }
+  }
+
+  /** add usage from a single model call or step */
+  add(delta: UsageCounts): void {
+    this.counts = {
+      inputTokens: this.counts.inputTokens + (delta.inputTokens ?? 0),
+      outputTokens: this.counts.outputTokens + (delta.outputTokens ?? 0),
+      cacheReadTokens: this.counts.cacheReadTokens + (delta.cacheReadTokens ?? 0),
+      cacheWriteTokens: this.counts.cacheWriteTokens + (delta.cacheWriteTokens ?? 0),
+      costUsd:
+        delta.costUsd === undefined || delta.costUsd === null
+          ? this.counts.costUsd
+          : (this.counts.costUsd ?? 0) + delta.costUsd,
+    };
+    this.check();
+  }
+
+  /** replace the counts with run totals reported by the agent */
+  set(totals: UsageCounts): void {
+    this.counts = { ...this.counts, ...totals };
+    this.check();
+  }
+
+  usage(): AgentUsage {
+    return { ...this.counts, durationMs: Date.now() - this.startTime };
+  }
+
+  dispose(): void {
+    clearTimeout(this.timeoutId);
+  }
+
+  private check(): void {
+    const { maxUsd, maxTokens } = this.budget;
+    const tokens = this.counts.inputTokens + this.counts.outputTokens;
+    if (maxUsd !== undefined && this.counts.costUsd !== null && this.counts.costUsd > maxUsd) {
+      this.abort(`the run cost $${this.counts.costUsd.toFixed(2)}, over its $${maxUsd} limit`);
+    } else if (maxTokens !== undefined && tokens > maxTokens) {
+      this.abort(`the run used ${tokens} tokens, over its ${maxTokens} token limit`);
+    }
+  }
+
+  private abort(reason: string): void {
+    if (this.exceededReason) return;
+    this.exceededReason = reason;
+    log.warning(`» budget exceeded: ${reason}. stopping the agent...`);
+    this.controller.abort(new Error(`budget exceeded: ${reason}`));
+  }
+}
@@ -0,0 +1,28 @@
+import type { OctokitWithPlugins } from "./github.ts";
+
+/**
+ * Read a file from the repo's default branch through the GitHub API.
+ * Config that decides what the agent may do is read from here rather than the working tree,
+ * which holds the PR's code on PR runs and which the agent can write to.
+ * Returns null when the file doesn't exist on the default branch.
+ */
+export async function readDefaultBranchFile(params: {
+  octokit: OctokitWithPlugins;
+  owner: string;
+  name: string;
+  path: string;
+}): Promise<string | null> {
+  try {
+    // without a ref, the contents API reads the default branch
+    const { data } = await params.octokit.rest.repos.getContent({
+      owner: params.owner,
+      repo: params.name,
+      path: params.path,
+    });
+    if (Array.isArray(data) || data.type !== "file" || !("content" in data)) return null;
+    return Buffer.from(data.content, "base64").toString("utf-8");
+  } catch (error) {
+    if ((error as { status?: number }).status === 404) return null;
+    throw error;
+  }
+}
@@ -1,4 +1,5 @@
 import type { ToolState } from "../mcp/server.ts";
+import { filterOutboundContent } from "../mcp/shared.ts";
 import { createOctokit, parseRepoContext } from "./github.ts";
 import { getGitHubInstallationToken } from "./token.ts";
 
@@ -16,6 +17,16 @@ export async function reportErrorToComment(ctx: ReportErrorParams): Promise<void
     return;
   }
 
+  // errors can quote agent output, so they're filtered like anything else posted to GitHub
+  let body: string;
+  try {
+    body = filterOutboundContent(ctx, "error report", formattedError);
+  } catch {
+    body =
+      `${ctx.title ?? "This run failed."}\n\n` +
+      "The error details were withheld because they appear to contain secrets. See the workflow run logs.";
+  }
+
   const repoContext = parseRepoContext();
   const octokit = createOctokit(getGitHubInstallationToken());
 
@@ -23,6 +34,6 @@ export async function reportErrorToComment(ctx: ReportErrorParams): Promise<void
     owner: repoContext.owner,
     repo: repoContext.name,
     comment_id: commentId,
-    body: formattedError,
+    body,
   });
 }
@@ -4,6 +4,8 @@ import * as core from "@actions/core";
 import { throttling } from "@octokit/plugin-throttling";
 import { Octokit } from "@octokit/rest";
 import { log } from "./cli.ts";
+import { getGitHubApiUrl } from "./githubHost.ts";
+import { isSelfHosted } from "./selfHosted.ts";
 import { retry } from "./retry.ts";
 
 export interface InstallationToken {
@@ -151,7 +153,7 @@ const githubRequest = async <T>(
 ): Promise<T> => {
   const { method = "GET", headers = {}, body } = options;
 
-  const url = `https://api.github.com${path}`;
+  const url = `${getGitHubApiUrl()}${path}`;
   const requestHeaders = {
     Accept: "application/vnd.github.v3+json",
     "User-Agent": "Pullfrog-Installation-Token-Generator/1.0",
@@ -230,9 +232,14 @@ const findInstallationId = async (
   );
 };
 
-// for local development only
+// used for local development and in self-hosted mode
 async function acquireTokenViaGitHubApp(): Promise<string> {
   const repoContext = parseRepoContext();
+  if (!process.env.GITHUB_APP_ID || !process.env.GITHUB_PRIVATE_KEY) {
+    throw new Error(
+      "GITHUB_APP_ID and GITHUB_PRIVATE_KEY are required to mint installation tokens without the Pullfrog API"
+    );
+  }
 
   const config: GitHubAppConfig = {
     appId: process.env.GITHUB_APP_ID!,
@@ -249,7 +256,7 @@ async function acquireTokenViaGitHubApp(): Promise<string> {
 }
 
 export async function acquireNewToken(opts?: { repos?: string[] }): Promise<string> {
-  if (isOIDCAvailable()) {
+  if (isOIDCAvailable() && !isSelfHosted()) {
     return await retry(() => acquireTokenViaOIDC(opts), { label: "token exchange" });
   } else {
     return await acquireTokenViaGitHubApp();
@@ -288,6 +295,7 @@ export function createOctokit(token: string): OctokitWithPlugins {
   const OctokitWithPlugins = Octokit.plugin(throttling);
   return new OctokitWithPlugins({
     auth: token,
+    baseUrl: getGitHubApiUrl(),
     throttle: {
       onRateLimit: (_retryAfter, _options, _octokit, retryCount) => {
         return retryCount <= 2;
@@ -0,0 +1,53 @@
+/**
+ * GitHub host resolution for github.com and GitHub Enterprise Server.
+ * Actions sets GITHUB_SERVER_URL and GITHUB_API_URL on every runner; outside Actions both
+ * default to github.com.
+ */
+
+const DEFAULT_SERVER_URL = "https://github.com";
+const DEFAULT_API_URL = "https://api.github.com";
+
+/**
+ * web URL of the GitHub instance, without a trailing slash (e.g. `https://ghes.example.com`)
+ */
+export function getGitHubServerUrl(): string {
+  return (process.env.GITHUB_SERVER_URL || DEFAULT_SERVER_URL).replace(/\/+$/, "");
+}
+
+/**
+ * REST API URL of the GitHub instance. GHES serves it under `/api/v3` when GITHUB_API_URL isn't set.
+ */
+export function getGitHubApiUrl(): string {
+  const apiUrl = process.env.GITHUB_API_URL;
+  if (apiUrl) return apiUrl.replace(/\/+$/, "");
+  const serverUrl = getGitHubServerUrl();
+  return serverUrl === DEFAULT_SERVER_URL ? DEFAULT_API_URL : `${serverUrl}/api/v3`;
+}
+
+export function isGitHubDotCom(): boolean {
+  return getGitHubServerUrl() === DEFAULT_SERVER_URL;
+}
+
+/**
+ * web URL for a path on the GitHub instance, e.g. `owner/repo/actions/runs/1`
+ */
+export function gitHubWebUrl(path: string): string {
+  return `${getGitHubServerUrl()}/${path.replace(/^\/+/, "")}`;
+}
+
+/**
+ * HTTPS clone/push URL authenticated with an installation token
+ */
+export function authenticatedRemoteUrl(token: string, fullName: string): string {
+  const url = new URL(`${getGitHubServerUrl()}/${fullName}.git`);
+  url.username = "x-access-token";
+  url.password = token;
+  return url.toString();
+}
+
+/**
+ * SSH remote URL, e.g. `git@github.com:owner/repo.git`
+ */
+export function sshRemoteUrl(fullName: string): string {
+  return `git@${new URL(getGitHubServerUrl()).hostname}:${fullName}.git`;
+}
@@ -0,0 +1,34 @@
+import { globToRegExp, matchesGlobs } from './glob.ts';
+
+describe('globToRegExp', () => {
+  it.each([
+    ['apps/*', 'apps/web', true],
+    ['apps/*', 'apps/web/src', false],
+    ['./apps/*', 'apps/web', true],
+    ['packages/**', 'packages/a/b/c', true],
+    ['**/*.ts', 'index.ts', true],
+    ['**/*.ts', 'src/deep/index.ts', true],
+    ['.github/workflows/**', '.github/workflows/ci.yml', true],
+    ['migrations/**', 'src/migrations/001.sql', false],
+    ['*.{yml,yaml}', 'config.yaml', true],
+    ['*.{yml,yaml}', 'config.json', false],
+    ['file?.txt', 'file1.txt', true],
+    ['file[0-9].txt', 'filea.txt', false],
+    ['a.b', 'axb', false],
+  ] as const)('%s matches %s: %s', (glob, path, expected) => {
+    expect(globToRegExp(glob).test(path)).toBe(expected);
+  });
+});
+
+describe('matchesGlobs', () => {
+  it('applies negations in order', () => {
+    const globs = ['packages/*', '!packages/legacy'];
+    expect(matchesGlobs('packages/core', globs)).toBe(true);
+    expect(matchesGlobs('packages/legacy', globs)).toBe(false);
+    expect(matchesGlobs('apps/web', globs)).toBe(false);
+  });
+
+  it('ignores a leading ./ on the path', () => {
+    expect(matchesGlobs('./apps/web', ['apps/*'])).toBe(true);
+  });
+});
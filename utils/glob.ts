@@ -0,0 +1,77 @@
+/**
+ * Minimal glob matching for repo-relative paths (workspace globs, path policies).
+ * Supports `*`, `**`, `?`, `{a,b}` and character classes; paths always use `/`.
+ */
+
+const regexCache = new Map<string, RegExp>();
+
+export function globToRegExp(glob: string): RegExp {
+  const cached = regexCache.get(glob);
+  if (cached) return cached;
+
+  // "./apps/*" and "apps/*/" are the same pattern as "apps/*"
+  const pattern = glob.replace(/^\.\//, "").replace(/\/+$/, "");
+  let source = "";
+  let braceDepth = 0;
+
+  for (let i = 0; i < pattern.length; i++) {
+    const char = pattern[i];
+    if (char === "*") {
+      if (pattern[i + 1] === "*") {
+        // "**/" matches zero or more directories, a trailing "**" matches everything below
+        const atSegmentStart = i === 0 || pattern[i - 1] === "/";
+        i++;
+        if (pattern[i + 1] === "/" && atSegmentStart) {
+          i++;
+          source += "(?:.*/)?";
+        } else {
+          source += ".*";
+        }
+      } else {
+        source += "[^/]*";
+      }
+    } else if (char === "?") {
+      source += "[^/]";
+    } else if (char === "[") {
+      const end = pattern.indexOf("]", i + 1);
+      if (end === -1) {
+        source += "\\[";
+      } else {
+        const body = pattern.slice(i + 1, end).replace(/^!/, "^").replace(/\\/g, "\\\\");
+        source += `[${body}]`;
+        i = end;
+      }
+    } else if (char === "{") {
+      braceDepth++;
+      source += "(?:";
+    } else if (char === "}" && braceDepth > 0) {
+      braceDepth--;
+      source += ")";
+    } else if (char === "," && braceDepth > 0) {
+      source += "|";
+    } else {
+      source += char.replace(/[.+^${}()|[\]\\]/g, "\\$&");
+    }
+  }
+
+  const regex = new RegExp(`^${source}$`);
+  regexCache.set(glob, regex);
+  return regex;
+}
+
+/**
+ * check a repo-relative path against a list of globs.
+ * patterns starting with "!" exclude paths matched by earlier patterns (gitignore-style, last match wins).
+ */
+export function matchesGlobs(path: string, globs: string[]): boolean {
+  const normalized = path.replace(/^\.\//, "");
+  let matched = false;
+  for (const glob of globs) {
+    const negated = glob.startsWith("!");
+    const regex = globToRegExp(negated ? glob.slice(1) : glob);
+    if (regex.test(normalized)) {
+      matched = !negated;
+    }
+  }
+  return matched;
+}
@@ -5,6 +5,7 @@ import { tmpdir } from "node:os";
 import { join } from "node:path";
 import { pipeline } from "node:stream/promises";
 import { log } from "./cli.ts";
+import { isGitHubDotCom } from "./githubHost.ts";
 
 export interface InstallFromNpmTarballParams {
   packageName: string;
@@ -186,7 +187,8 @@ export async function installFromGithub(params: InstallFromGithubParams): Promis
   log.debug(`» fetching release from ${releaseUrl}...`);
 
   const headers: Record<string, string> = {};
-  if (params.githubInstallationToken) {
+  // agent CLIs are always released on github.com; a GHES installation token isn't valid there
+  if (params.githubInstallationToken && isGitHubDotCom()) {
     headers.Authorization = `Bearer ${params.githubInstallationToken}`;
   }
 
@@ -266,7 +268,8 @@ export async function installFromGithubTarball(
   log.info(`» fetching release from ${releaseUrl}...`);
 
   const headers: Record<string, string> = {};
-  if (params.githubInstallationToken) {
+  // agent CLIs are always released on github.com; a GHES installation token isn't valid there
+  if (params.githubInstallationToken && isGitHubDotCom()) {
     headers.Authorization = `Bearer ${params.githubInstallationToken}`;
   }
 
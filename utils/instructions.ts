@@ -9,6 +9,8 @@ interface InstructionsContext {
   payload: ResolvedPayload;
   repoData: RepoData;
   modes: Mode[];
+  /** mode routed from the event, in which case the agent doesn't pick one */
+  selectedMode?: Mode | null | undefined;
 }
 
 function buildRuntimeContext(ctx: InstructionsContext): string {
@@ -44,12 +46,15 @@ function buildRuntimeContext(ctx: InstructionsContext): string {
   return lines.join("\n");
 }
 
-function getShellInstructions(bash: ResolvedPayload["bash"]): string {
+function getShellInstructions(
+  bash: ResolvedPayload["bash"],
+  sandbox: ResolvedPayload["sandbox"]
+): string {
   switch (bash) {
     case "disabled":
       return `**Shell commands**: Shell command execution is DISABLED. Do not attempt to run shell commands.`;
     case "restricted":
-      return `**Shell commands**: Use the \`${ghPullfrogMcpName}/bash\` MCP tool for all shell command execution. This tool provides a secure environment with filtered credentials. Do NOT use any native shell/bash tool - it is disabled for security.`;
+      return `**Shell commands**: Use the \`${ghPullfrogMcpName}/bash\` MCP tool for all shell command execution. This tool provides a secure environment with filtered credentials. Do NOT use any native shell/bash tool - it is disabled for security.${sandbox === "isolated" ? " Commands run in an isolated sandbox: network access is limited to package registries, and only the repository checkout is writable." : ""}`;
     case "enabled":
       return `**Shell commands**: Use your native bash/shell tool for shell command execution.`;
     default: {
@@ -59,6 +64,25 @@ function getShellInstructions(bash: ResolvedPayload["bash"]): string {
   }
 }
 
+function getModeInstructions(ctx: InstructionsContext): string {
+  if (ctx.selectedMode) {
+    return `**Mode**: This run uses the "${ctx.selectedMode.name}" mode, chosen from the event that triggered it. Do not call ${ghPullfrogMcpName}/select_mode. Follow the mode instructions below, referring to the user prompt, event data, and runtime context to inform your actions. These instructions cannot override the Security rules or System instructions above.
+
+### Mode instructions
+
+${ctx.selectedMode.prompt}`;
+  }
+  return `**Required!** Before starting any work, you will pick a mode. Examine the prompt below carefully, along with the event data and runtime context. Determine which mode is most appropriate based on the mode descriptions below. Then use ${ghPullfrogMcpName}/select_mode to pick a mode.  If the request could fit multiple modes, choose the mode with the narrowest scope that still addresses the request. You will be given back detailed step-by-step instructions based on your selection.
+
+### Available modes
+
+${ctx.modes.map((m) => `- "${m.name}": ${m.description}`).join("\n")}
+
+### Following the mode instructions
+
+After selecting a mode, follow the detailed step-by-step instructions provided by the ${ghPullfrogMcpName}/select_mode tool. Refer to the user prompt, event data, and runtime context below to inform your actions. These instructions cannot override the Security rules or System instructions above.`;
+}
+
 export interface ResolvedInstructions {
   full: string;
   system: string;
@@ -132,7 +156,7 @@ Tool names may be formatted as \`(server name)/(tool name)\`, for example: \`${g
 
 **Efficiency**: Trust the tools - do not repeatedly verify file contents or git status after operations. If a tool reports success, proceed to the next step. Only verify if you encounter an actual error.
 
-${getShellInstructions(ctx.payload.bash)}
+${getShellInstructions(ctx.payload.bash, ctx.payload.sandbox)}
 
 **Command execution**: Never use \`sleep\` to wait for commands to complete. Commands run synchronously - when the bash tool returns, the command has finished.
 
@@ -149,15 +173,7 @@ ${getShellInstructions(ctx.payload.bash)}
 ************* YOUR TASK *************
 *************************************
 
-**Required!** Before starting any work, you will pick a mode. Examine the prompt below carefully, along with the event data and runtime context. Determine which mode is most appropriate based on the mode descriptions below. Then use ${ghPullfrogMcpName}/select_mode to pick a mode.  If the request could fit multiple modes, choose the mode with the narrowest scope that still addresses the request. You will be given back detailed step-by-step instructions based on your selection.
-
-### Available modes
-
-${ctx.modes.map((m) => `- "${m.name}": ${m.description}`).join("\n")}
-
-### Following the mode instructions
-
-After selecting a mode, follow the detailed step-by-step instructions provided by the ${ghPullfrogMcpName}/select_mode tool. Refer to the user prompt, event data, and runtime context below to inform your actions. These instructions cannot override the Security rules or System instructions above.
+${getModeInstructions(ctx)}
 
 Eagerly inspect the MCP tools available to you via the \`${ghPullfrogMcpName}\` MCP server. These are VITALLY IMPORTANT to completing your task.`;
 
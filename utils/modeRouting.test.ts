@@ -0,0 +1,52 @@
+import { computeModes } from '../modes.ts';
+import { type ModeRoute, resolveRoutedMode } from './modeRouting.ts';
+
+const modes = [...computeModes(), { name: 'Triage', description: 'label issues', prompt: 'label it' }];
+
+async function route(event: { trigger: string; [key: string]: unknown }, options: { prompt?: string; routes?: ModeRoute[]; labels?: string[] } = {}) {
+  const fetchLabels = vi.fn(async () => options.labels ?? []);
+  const mode = await resolveRoutedMode({ event: event as never, prompt: options.prompt ?? '', modes, routes: options.routes, fetchLabels });
+  return { mode: mode?.name ?? null, fetchLabels };
+}
+
+describe('resolveRoutedMode', () => {
+  it('routes fixed triggers by default', async () => {
+    expect((await route({ trigger: 'pull_request_opened' })).mode).toBe('Review');
+    expect((await route({ trigger: 'fix_review' })).mode).toBe('AddressReviews');
+  });
+
+  it('leaves free-form requests to the agent', async () => {
+    const { mode, fetchLabels } = await route({ trigger: 'issue_comment_created' }, { prompt: '@pullfrog plan this' });
+    expect(mode).toBeNull();
+    expect(fetchLabels).not.toHaveBeenCalled();
+  });
+
+  it('checks repo routes before the defaults', async () => {
+    const routes = [{ mode: 'Triage', triggers: ['pull_request_opened'] }];
+    expect((await route({ trigger: 'pull_request_opened' }, { routes })).mode).toBe('Triage');
+  });
+
+  it('requires every criterion of a route', async () => {
+    const routes = [{ mode: 'plan', triggers: ['issue_comment_created'], keywords: ['plan'], labels: ['needs-plan'] }];
+    const event = { trigger: 'issue_comment_created', issue_number: 4 };
+    expect((await route(event, { routes, prompt: 'please PLAN this', labels: ['needs-plan'] })).mode).toBe('Plan');
+    expect((await route(event, { routes, prompt: 'look at the planet', labels: ['needs-plan'] })).mode).toBeNull();
+    expect((await route(event, { routes, prompt: 'please plan this', labels: ['bug'] })).mode).toBeNull();
+  });
+
+  it('routes without labels when fetching them fails', async () => {
+    const routes = [{ mode: 'Plan', labels: ['needs-plan'] }];
+    const fetchLabels = vi.fn(async (): Promise<string[]> => {
+      throw new Error('Resource not accessible by integration');
+    });
+    const params = { prompt: '', modes, routes, fetchLabels };
+    expect(await resolveRoutedMode({ ...params, event: { trigger: 'issue_comment_created', issue_number: 4 } as never })).toBeNull();
+    expect((await resolveRoutedMode({ ...params, event: { trigger: 'pull_request_opened' } as never }))?.name).toBe('Review');
+    expect(fetchLabels).toHaveBeenCalledTimes(2);
+  });
+
+  it('skips routes to unknown modes', async () => {
+    const routes = [{ mode: 'Deploy', triggers: ['pull_request_opened'] }];
+    expect((await route({ trigger: 'pull_request_opened' }, { routes })).mode).toBe('Review');
+  });
+});
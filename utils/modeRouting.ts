@@ -0,0 +1,99 @@
+import { type } from "arktype";
+import type { PayloadEvent } from "../external.ts";
+import type { Mode } from "../modes.ts";
+import { log } from "./cli.ts";
+
+// arktype schema for a routing rule. every criterion given must match; within a criterion any
+// listed value matches. a rule without criteria matches every run.
+export const ModeRouteSchema = type({
+  "+": "reject",
+  mode: "string > 0",
+  "triggers?": "string[]",
+  "labels?": "string[]",
+  "keywords?": "string[]",
+});
+
+export type ModeRoute = typeof ModeRouteSchema.infer;
+
+/**
+ * events whose mode never depends on what the user wrote. repo routes are checked first,
+ * so they can send these triggers to other modes.
+ */
+export const DEFAULT_MODE_ROUTES: ModeRoute[] = [
+  {
+    mode: "Review",
+    triggers: [
+      "pull_request_opened",
+      "pull_request_ready_for_review",
+      "pull_request_review_requested",
+    ],
+  },
+  { mode: "AddressReviews", triggers: ["fix_review"] },
+  { mode: "Build", triggers: ["implement_plan"] },
+];
+
+interface RouteInput {
+  event: PayloadEvent;
+  prompt: string;
+  labels: string[];
+}
+
+function escapeRegExp(value: string): string {
+  return value.replace(/[.*+?^${}()|[\]\\]/g, "\\$&");
+}
+
+function matchesRoute(route: ModeRoute, input: RouteInput): boolean {
+  if (route.triggers && !route.triggers.includes(input.event.trigger)) return false;
+  if (route.labels && !route.labels.some((label) => input.labels.includes(label))) return false;
+  if (
+    route.keywords &&
+    !route.keywords.some((keyword) =>
+      new RegExp(`\\b${escapeRegExp(keyword)}\\b`, "i").test(input.prompt)
+    )
+  ) {
+    return false;
+  }
+  return true;
+}
+
+interface ResolveRoutedModeParams {
+  event: PayloadEvent;
+  prompt: string;
+  modes: Mode[];
+  /** repo routes, checked before DEFAULT_MODE_ROUTES */
+  routes?: ModeRoute[] | undefined;
+  /** labels on the triggering issue or PR, only fetched when a route needs them */
+  fetchLabels: () => Promise<string[]>;
+}
+
+/**
+ * a failed label lookup shouldn't fail the run: label routes just don't match
+ */
+async function fetchLabelsOrNone(params: ResolveRoutedModeParams): Promise<string[]> {
+  try {
+    return await params.fetchLabels();
+  } catch (error) {
+    const message = error instanceof Error ? error.message : String(error);
+    log.warning(`» failed to fetch labels for mode routing, routing without them: ${message}`);
+    return [];
+  }
+}
+
+/**
+ * Pick the mode for a run from the first matching route, or null to let the agent choose with select_mode.
+ */
+export async function resolveRoutedMode(params: ResolveRoutedModeParams): Promise<Mode | null> {
+  const routes = [...(params.routes ?? []), ...DEFAULT_MODE_ROUTES];
+  const labels = routes.some((route) => route.labels) ? await fetchLabelsOrNone(params) : [];
+
+  for (const route of routes) {
+    if (!matchesRoute(route, { event: params.event, prompt: params.prompt, labels })) continue;
+    const mode = params.modes.find((m) => m.name.toLowerCase() === route.mode.toLowerCase());
+    if (!mode) {
+      log.warning(`» mode route points to unknown mode "${route.mode}", skipping`);
+      continue;
+    }
+    return mode;
+  }
+  return null;
+}
@@ -21,6 +21,13 @@ describe('Inputs schema', () => {
     ['bash', 'restricted'],
     ['bash', 'disabled'],
     ['bash', undefined],
+    ['sandbox', 'standard'],
+    ['sandbox', 'isolated'],
+    ['sandbox', undefined],
+    ['allowed_hosts', 'internal.example.com'],
+    ['max_usd', '2.5'],
+    ['max_tokens', '500000'],
+    ['max_minutes', '30'],
     ['effort', 'mini'],
     ['effort', 'auto'],
     ['effort', 'max'],
@@ -41,6 +48,7 @@ describe('Inputs schema', () => {
     ['search'],
     ['write'],
     ['bash'],
+    ['sandbox'],
     ['effort'],
     ['agent'],
   ] as const)('should reject invalid %s values', (prop) => {
@@ -8,11 +8,13 @@ import {
   Effort,
   type PayloadEvent,
 } from "../external.ts";
+import type { RunBudget } from "./budget.ts";
 import type { RepoSettings } from "./repoSettings.ts";
 
 // tool permission enum types for inputs
 const ToolPermissionInput = type.enumerated("disabled", "enabled");
 const BashPermissionInput = type.enumerated("disabled", "restricted", "enabled");
+const SandboxModeInput = type.enumerated("standard", "isolated");
 
 // schema for JSON payload passed via prompt (internal dispatch invocation)
 // note: permissions are intentionally NOT included here to prevent injection attacks
@@ -47,6 +49,11 @@ export const Inputs = type({
   "search?": ToolPermissionInput.or("undefined"),
   "write?": ToolPermissionInput.or("undefined"),
   "bash?": BashPermissionInput.or("undefined"),
+  "sandbox?": SandboxModeInput.or("undefined"),
+  "allowed_hosts?": "string|undefined",
+  "max_usd?": "string|undefined",
+  "max_tokens?": "string|undefined",
+  "max_minutes?": "string|undefined",
   "cwd?": "string|null",
 });
 
@@ -67,6 +74,33 @@ function resolveCwd(cwd: string | null | undefined): string | null {
   return workspace ? resolve(workspace, cwd) : cwd;
 }
 
+// comma- or newline-separated host list (action inputs are plain strings)
+function parseHostList(value: string | undefined): string[] | undefined {
+  if (!value) return undefined;
+  return value
+    .split(/[,\n]/)
+    .map((host) => host.trim())
+    .filter(Boolean);
+}
+
+// numeric budget input; empty means no limit
+function parseLimit(name: string, value: string | undefined): number | undefined {
+  if (!value) return undefined;
+  const limit = Number(value);
+  if (!Number.isFinite(limit) || limit <= 0) {
+    throw new Error(`invalid ${name} input: expected a positive number, got "${value}"`);
+  }
+  return limit;
+}
+
+function resolveBudget(inputs: Inputs, repoSettings: RepoSettings): RunBudget {
+  return {
+    maxUsd: parseLimit("max_usd", inputs.max_usd) ?? repoSettings.budget?.maxUsd,
+    maxTokens: parseLimit("max_tokens", inputs.max_tokens) ?? repoSettings.budget?.maxTokens,
+    maxMinutes: parseLimit("max_minutes", inputs.max_minutes) ?? repoSettings.budget?.maxMinutes,
+  };
+}
+
 export function resolvePayload(repoSettings: RepoSettings) {
   const inputs = Inputs.assert({
     prompt: core.getInput("prompt", { required: true }),
@@ -77,6 +111,11 @@ export function resolvePayload(repoSettings: RepoSettings) {
     search: core.getInput("search") || undefined,
     write: core.getInput("write") || undefined,
     bash: core.getInput("bash") || undefined,
+    sandbox: core.getInput("sandbox") || undefined,
+    allowed_hosts: core.getInput("allowed_hosts") || undefined,
+    max_usd: core.getInput("max_usd") || undefined,
+    max_tokens: core.getInput("max_tokens") || undefined,
+    max_minutes: core.getInput("max_minutes") || undefined,
   });
 
   // convert "null" string to null, validate agent name
@@ -133,6 +172,11 @@ export function resolvePayload(repoSettings: RepoSettings) {
     search: inputs.search ?? repoSettings.search ?? "enabled",
     write: inputs.write ?? repoSettings.write ?? "enabled",
     bash: inputs.bash ?? (shouldRestrict ? "restricted" : repoSettings.bash) ?? "restricted",
+    // only applies when bash resolves to "restricted"
+    sandbox: inputs.sandbox ?? repoSettings.sandbox ?? "standard",
+    allowedHosts: parseHostList(inputs.allowed_hosts) ?? repoSettings.allowedHosts ?? [],
+    // budgets: inputs > repoSettings, per limit
+    budget: resolveBudget(inputs, repoSettings),
   };
 }
 
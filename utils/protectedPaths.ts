@@ -0,0 +1,47 @@
+import { matchesGlobs } from "./glob.ts";
+import type { RepoSettings } from "./repoSettings.ts";
+
+// CI configuration, pullfrog's own config and ownership rules are protected unless the repo
+// overrides the list. lockfiles are left out: dependency bumps are routine agent work
+export const DEFAULT_PROTECTED_PATHS = [
+  ".github/workflows/**",
+  ".github/actions/**",
+  ".pullfrog/**",
+  ".pullfrog.yml",
+  "CODEOWNERS",
+  ".github/CODEOWNERS",
+  "docs/CODEOWNERS",
+];
+
+export const DEFAULT_PROTECTED_PATHS_LABEL = "pullfrog:allow-protected";
+
+/**
+ * commits touching protected paths are either blocked outright ("block") or allowed only
+ * when the triggering issue/PR carries `label` ("label")
+ */
+export interface ProtectedPathsSettings {
+  patterns?: string[] | undefined;
+  mode?: "block" | "label" | undefined;
+  label?: string | undefined;
+}
+
+export interface ResolvedProtectedPaths {
+  patterns: string[];
+  mode: "block" | "label";
+  label: string;
+}
+
+export function resolveProtectedPaths(
+  repoSettings: Pick<RepoSettings, "protectedPaths">
+): ResolvedProtectedPaths {
+  const settings = repoSettings.protectedPaths;
+  return {
+    patterns: settings?.patterns ?? DEFAULT_PROTECTED_PATHS,
+    mode: settings?.mode ?? "label",
+    label: settings?.label ?? DEFAULT_PROTECTED_PATHS_LABEL,
+  };
+}
+
+export function findProtectedPaths(paths: string[], patterns: string[]): string[] {
+  return paths.filter((path) => matchesGlobs(path, patterns));
+}
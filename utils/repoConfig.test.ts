@@ -0,0 +1,158 @@
+import { mkdirSync, mkdtempSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { dirname, join } from 'node:path';
+import type { OctokitWithPlugins } from './github.ts';
+import { loadRepoConfig, type RepoConfigError, repoConfigToSettings } from './repoConfig.ts';
+import { DEFAULT_REPO_SETTINGS, mergeRepoSettings, resolveRepoSettings } from './repoSettings.ts';
+import { fetchWorkflowRunInfo } from './workflowRun.ts';
+
+// serves files as the contents API would from the default branch
+function octokitServing(files: Record<string, string>): OctokitWithPlugins {
+  const getContent = async ({ path }: { path: string }) => {
+    const content = files[path];
+    if (content === undefined) throw Object.assign(new Error('Not Found'), { status: 404 });
+    return { data: { type: 'file', path, content: Buffer.from(content).toString('base64') } };
+  };
+  return { rest: { repos: { getContent } } } as unknown as OctokitWithPlugins;
+}
+
+describe('loadRepoConfig', () => {
+  let repo: string;
+
+  const writeConfig = (content: string, file = '.pullfrog.yml') => {
+    mkdirSync(dirname(join(repo, file)), { recursive: true });
+    writeFileSync(join(repo, file), content);
+  };
+
+  beforeEach(() => {
+    repo = mkdtempSync(join(tmpdir(), 'pullfrog-repo-config-test-'));
+  });
+
+  afterEach(() => {
+    vi.unstubAllEnvs();
+    vi.restoreAllMocks();
+    rmSync(repo, { recursive: true, force: true });
+  });
+
+  it('returns null without a config file', () => {
+    expect(loadRepoConfig(repo)).toBeNull();
+  });
+
+  it('loads settings and custom modes', () => {
+    writeConfig(
+      ['defaultAgent: codex', 'bash: enabled', 'modes:', '  - name: Triage', '    description: Label new issues', '    prompt: |', '      Add labels.'].join('\n')
+    );
+    const loaded = loadRepoConfig(repo);
+    expect(loaded).toEqual({
+      file: '.pullfrog.yml',
+      config: {
+        defaultAgent: 'codex',
+        bash: 'enabled',
+        modes: [{ name: 'Triage', description: 'Label new issues', prompt: 'Add labels.\n' }],
+      },
+    });
+    expect(repoConfigToSettings(loaded!.config).modes).toEqual([
+      { id: 'Triage', name: 'Triage', description: 'Label new issues', prompt: 'Add labels.\n' },
+    ]);
+  });
+
+  it('loads checks and the secrets allowlist', () => {
+    writeConfig(
+      ['checks:', '  - name: unit', '    kind: test', '    run: just test', 'secretsAllowlist:', '  paths:', '    - fixtures/**'].join('\n')
+    );
+    expect(loadRepoConfig(repo)?.config).toEqual({
+      checks: [{ name: 'unit', kind: 'test', run: 'just test' }],
+      secretsAllowlist: { paths: ['fixtures/**'] },
+    });
+  });
+
+  it('prefers .pullfrog.yml over .pullfrog/config.yml', () => {
+    writeConfig('write: disabled\n', '.pullfrog/config.yml');
+    expect(loadRepoConfig(repo)?.file).toBe('.pullfrog/config.yml');
+    writeConfig('write: enabled\n');
+    expect(loadRepoConfig(repo)).toEqual({ file: '.pullfrog.yml', config: { write: 'enabled' } });
+  });
+
+  it('lists every schema error', () => {
+    writeConfig(
+      ['bash: sometimes', 'defaultAgnet: claude', 'protectedPaths:', '  mode: warn', 'checks:', '  - name: unit', 'secretsAllowlist:', '  value: x'].join('\n')
+    );
+    let problems: string[] = [];
+    try {
+      loadRepoConfig(repo);
+    } catch (error) {
+      problems = (error as RepoConfigError).problems;
+    }
+    expect(problems).toEqual(
+      expect.arrayContaining([
+        expect.stringMatching(/^bash /),
+        expect.stringMatching(/^defaultAgnet /),
+        expect.stringMatching(/^protectedPaths\.mode /),
+        expect.stringMatching(/^checks\[0\]\.kind /),
+        expect.stringMatching(/^secretsAllowlist\.value /),
+      ])
+    );
+  });
+
+  it('reports YAML syntax errors', () => {
+    writeConfig('bash: [\n');
+    expect(() => loadRepoConfig(repo)).toThrow('.pullfrog.yml is invalid:\n  - not valid YAML: ');
+  });
+
+  it('reads flow mappings and validates scalar types through the schema', () => {
+    writeConfig('writePolicy: { deny: [migrations/**] }\nallowedHosts: [1.20]\n');
+    expect(() => loadRepoConfig(repo)).toThrow(/allowedHosts\[0\] must be a string/);
+    writeConfig('writePolicy: { deny: [migrations/**] }\n');
+    expect(loadRepoConfig(repo)?.config).toEqual({ writePolicy: { deny: ['migrations/**'] } });
+  });
+
+  it('never calls the Pullfrog API in self-hosted mode', async () => {
+    vi.stubEnv('PULLFROG_SELF_HOSTED', 'true');
+    const fetchSpy = vi.spyOn(globalThis, 'fetch');
+
+    const octokit = octokitServing({ '.pullfrog.yml': 'write: disabled\n' });
+    const settings = await resolveRepoSettings({ octokit, token: 'token', repoContext: { owner: 'acme', name: 'app' } });
+    expect(settings).toMatchObject({ write: 'disabled', bash: 'restricted', modes: [] });
+    expect(await fetchWorkflowRunInfo('123')).toEqual({ progressCommentId: null });
+    expect(fetchSpy).not.toHaveBeenCalled();
+  });
+
+  it('reads the config file from the default branch, not the working tree', async () => {
+    vi.stubEnv('PULLFROG_SELF_HOSTED', 'true');
+    vi.stubEnv('GITHUB_WORKSPACE', repo);
+    writeConfig('bash: enabled\nwritePolicy:\n  deny: []\n');
+
+    const octokit = octokitServing({ '.pullfrog/config.yml': 'bash: disabled\n' });
+    const settings = await resolveRepoSettings({ octokit, token: 'token', repoContext: { owner: 'acme', name: 'app' } });
+    expect(settings.bash).toBe('disabled');
+    expect(settings.writePolicy).toBeUndefined();
+  });
+
+  it('layers the config file over remote settings', async () => {
+    const octokit = octokitServing({
+      '.pullfrog.yml': ['bash: enabled', 'modes:', '  - name: Triage', '    description: from file', '    prompt: file'].join('\n'),
+    });
+    const remote = {
+      bash: 'disabled',
+      web: 'disabled',
+      modes: [
+        { id: 'r1', name: 'Triage', description: 'from remote', prompt: 'remote' },
+        { id: 'r2', name: 'Release', description: 'from remote', prompt: 'remote' },
+      ],
+    };
+    vi.spyOn(globalThis, 'fetch').mockResolvedValue(new Response(JSON.stringify(remote)));
+
+    const settings = await resolveRepoSettings({ octokit, token: 'token', repoContext: { owner: 'acme', name: 'app' } });
+    expect(settings).toMatchObject({ bash: 'enabled', web: 'disabled', search: 'enabled' });
+    expect(settings.modes.map((mode) => [mode.name, mode.description])).toEqual([
+      ['Triage', 'from file'],
+      ['Release', 'from remote'],
+    ]);
+  });
+});
+
+describe('mergeRepoSettings', () => {
+  it('falls back to defaults when nothing overrides them', () => {
+    expect(mergeRepoSettings(DEFAULT_REPO_SETTINGS, null, null)).toEqual(DEFAULT_REPO_SETTINGS);
+  });
+});
@@ -0,0 +1,131 @@
+import { existsSync, readFileSync } from "node:fs";
+import { join } from "node:path";
+import { type } from "arktype";
+import { parse as parseYaml } from "yaml";
+import { AgentName } from "../external.ts";
+import { CheckSchema } from "../mcp/checks.ts";
+import { ModeSchema } from "../modes.ts";
+import { SetupStepSchema } from "../prep/setupScripts.ts";
+import { readDefaultBranchFile } from "./defaultBranch.ts";
+import type { OctokitWithPlugins } from "./github.ts";
+import { ModeRouteSchema } from "./modeRouting.ts";
+import type { RepoSettings } from "./repoSettings.ts";
+import { SecretsAllowlistSchema } from "./secretScanner.ts";
+
+// checked-in config locations, in lookup order
+export const REPO_CONFIG_FILES = [".pullfrog.yml", ".pullfrog/config.yml"];
+
+const ToolPermissionSchema = type.enumerated("disabled", "enabled");
+
+// arktype schema for the checked-in repo config. every field is optional; unknown keys are
+// rejected so typos don't silently fall back to remote settings.
+export const RepoConfigSchema = type({
+  "+": "reject",
+  "defaultAgent?": AgentName.or("null"),
+  "modes?": ModeSchema.array(),
+  "modeRoutes?": ModeRouteSchema.array(),
+  "web?": ToolPermissionSchema,
+  "search?": ToolPermissionSchema,
+  "write?": ToolPermissionSchema,
+  "bash?": type.enumerated("disabled", "restricted", "enabled"),
+  "sandbox?": type.enumerated("standard", "isolated"),
+  "allowedHosts?": "string[]",
+  "writePolicy?": { "+": "reject", deny: "string[]" },
+  "protectedPaths?": {
+    "+": "reject",
+    "patterns?": "string[]",
+    "mode?": type.enumerated("block", "label"),
+    "label?": "string > 0",
+  },
+  "budget?": {
+    "+": "reject",
+    "maxUsd?": "number > 0",
+    "maxTokens?": "number.integer > 0",
+    "maxMinutes?": "number > 0",
+  },
+  "prep?": SetupStepSchema.array(),
+  "rustPrebuild?": "boolean",
+  "checks?": CheckSchema.array(),
+  "secretsAllowlist?": SecretsAllowlistSchema,
+});
+
+export type RepoConfig = typeof RepoConfigSchema.infer;
+
+export interface LoadedRepoConfig {
+  /** repo-relative path of the file the config was read from */
+  file: string;
+  config: RepoConfig;
+}
+
+export class RepoConfigError extends Error {
+  readonly file: string;
+  readonly problems: string[];
+
+  constructor(file: string, problems: string[]) {
+    super(`${file} is invalid:\n${problems.map((problem) => `  - ${problem}`).join("\n")}`);
+    this.file = file;
+    this.problems = problems;
+  }
+}
+
+export function findRepoConfigFile(cwd: string): string | null {
+  return REPO_CONFIG_FILES.find((file) => existsSync(join(cwd, file))) ?? null;
+}
+
+/**
+ * parse and validate the contents of a repo config file.
+ * throws RepoConfigError listing every problem if it can't be parsed or doesn't match the schema.
+ */
+export function parseRepoConfig(file: string, source: string): RepoConfig {
+  let parsed: unknown;
+  try {
+    parsed = parseYaml(source);
+  } catch (error) {
+    // the first line names the problem and its position; the rest is a code frame
+    const message = error instanceof Error ? error.message.split("\n")[0] : String(error);
+    throw new RepoConfigError(file, [`not valid YAML: ${message}`]);
+  }
+  // an empty file means "no overrides"
+  const validated = RepoConfigSchema(parsed ?? {});
+  if (validated instanceof type.errors) {
+    throw new RepoConfigError(file, validated.map((error) => error.message));
+  }
+  return validated;
+}
+
+/**
+ * load the checked-in repo config from a local checkout. returns null if there is none.
+ * only for validating a checkout: runs read the config from the default branch (fetchRepoConfig)
+ */
+export function loadRepoConfig(cwd: string): LoadedRepoConfig | null {
+  const file = findRepoConfigFile(cwd);
+  if (!file) return null;
+  return { file, config: parseRepoConfig(file, readFileSync(join(cwd, file), "utf-8")) };
+}
+
+/**
+ * fetch the repo config from the default branch. the working tree holds the PR's code on PR runs,
+ * so a PR could otherwise grant itself bash, lift the write policy or raise the budget.
+ */
+export async function fetchRepoConfig(params: {
+  octokit: OctokitWithPlugins;
+  owner: string;
+  name: string;
+}): Promise<LoadedRepoConfig | null> {
+  for (const file of REPO_CONFIG_FILES) {
+    const source = await readDefaultBranchFile({ ...params, path: file });
+    if (source !== null) return { file, config: parseRepoConfig(file, source) };
+  }
+  return null;
+}
+
+/**
+ * convert the file's fields to repo settings (modes get their name as id)
+ */
+export function repoConfigToSettings(config: RepoConfig): Partial<RepoSettings> {
+  const { modes, ...rest } = config;
+  return {
+    ...rest,
+    ...(modes ? { modes: modes.map((mode) => ({ id: mode.name, ...mode })) } : {}),
+  };
+}
@@ -2,7 +2,7 @@ import type { Octokit } from "@octokit/rest";
 import packageJson from "../package.json" with { type: "json" };
 import { log } from "./cli.ts";
 import { createOctokit, type OctokitWithPlugins, parseRepoContext } from "./github.ts";
-import { fetchRepoSettings, type RepoSettings } from "./repoSettings.ts";
+import { type RepoSettings, resolveRepoSettings } from "./repoSettings.ts";
 
 export interface RepoData {
   owner: string;
@@ -27,7 +27,11 @@ export async function resolveRepoData(params: ResolveRepoDataParams): Promise<Re
   // fetch repo data and settings in parallel
   const [repoResponse, repoSettings] = await Promise.all([
     params.octokit.repos.get({ owner, repo: name }),
-    fetchRepoSettings({ token: params.token, repoContext: { owner, name } }),
+    resolveRepoSettings({
+      octokit: params.octokit,
+      token: params.token,
+      repoContext: { owner, name },
+    }),
   ]);
 
   return {
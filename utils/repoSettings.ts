@@ -1,30 +1,108 @@
-import type { AgentName, BashPermission, ToolPermission } from "../external.ts";
-import type { RepoContext } from "./github.ts";
+import type { AgentName, BashPermission, SandboxMode, ToolPermission } from "../external.ts";
+import type { Check } from "../mcp/checks.ts";
+import type { Mode as ModeDefinition } from "../modes.ts";
+import type { SetupStep } from "../prep/types.ts";
+import type { RunBudget } from "./budget.ts";
+import { log } from "./cli.ts";
+import type { OctokitWithPlugins, RepoContext } from "./github.ts";
+import type { ModeRoute } from "./modeRouting.ts";
+import type { ProtectedPathsSettings } from "./protectedPaths.ts";
+import { fetchRepoConfig, repoConfigToSettings } from "./repoConfig.ts";
+import { isSelfHosted } from "./selfHosted.ts";
+import type { WritePolicy } from "./writePolicy.ts";
 
-export interface Mode {
+export interface Mode extends ModeDefinition {
   id: string;
-  name: string;
-  description: string;
-  prompt: string;
 }
 
 export interface RepoSettings {
   defaultAgent: AgentName | null;
   modes: Mode[];
+  /** rules that pick a mode from the event instead of leaving it to the agent */
+  modeRoutes?: ModeRoute[] | undefined;
   web: ToolPermission;
   search: ToolPermission;
   write: ToolPermission;
   bash: BashPermission;
+  /** sandbox for restricted bash */
+  sandbox?: SandboxMode | undefined;
+  /** extra hosts reachable from the isolated sandbox (besides package registries) */
+  allowedHosts?: string[] | undefined;
+  /** paths the agent may never modify (enforced by commit_files, create_pull_request and bash) */
+  writePolicy?: WritePolicy | undefined;
+  /** paths (CI config and CODEOWNERS by default) that commits may only touch with approval */
+  protectedPaths?: ProtectedPathsSettings | undefined;
+  /** per-run cost, token and time limits */
+  budget?: RunBudget | undefined;
+  /** repo-defined setup steps run after dependency installation */
+  prep?: SetupStep[] | undefined;
+  /** run `cargo build --tests` after fetching crates so the agent's first `cargo test` is fast */
+  rustPrebuild?: boolean | undefined;
+  /** commands run_checks runs instead of discovering them */
+  checks?: Check[] | undefined;
+  /** values and path globs the secret scanner treats as known false positives */
+  secretsAllowlist?: { values?: string[] | undefined; paths?: string[] | undefined } | undefined;
+}
+
+export const DEFAULT_REPO_SETTINGS: RepoSettings = {
+  defaultAgent: null,
+  modes: [],
+  web: "enabled",
+  search: "enabled",
+  write: "enabled",
+  bash: "restricted",
+};
+
+/**
+ * layer partial settings over each other, later layers winning.
+ * custom modes are merged by name; every other key is replaced as a whole.
+ */
+export function mergeRepoSettings(
+  base: RepoSettings,
+  ...layers: (Partial<RepoSettings> | null)[]
+): RepoSettings {
+  let merged = base;
+  for (const layer of layers) {
+    if (!layer) continue;
+    const modes = new Map(merged.modes.map((mode) => [mode.name, mode]));
+    for (const mode of layer.modes ?? []) modes.set(mode.name, mode);
+    merged = { ...merged, ...layer, modes: [...modes.values()] };
+  }
+  return merged;
+}
+
+/**
+ * Resolve repository settings. Precedence, highest first:
+ * 1. action inputs (applied on top of these settings by resolvePayload)
+ * 2. the checked-in config file (.pullfrog.yml or .pullfrog/config.yml) on the default branch
+ * 3. remote settings from the Pullfrog API (never fetched in self-hosted mode)
+ * 4. DEFAULT_REPO_SETTINGS
+ */
+export async function resolveRepoSettings(params: {
+  octokit: OctokitWithPlugins;
+  token: string;
+  repoContext: RepoContext;
+}): Promise<RepoSettings> {
+  const loaded = await fetchRepoConfig({ octokit: params.octokit, ...params.repoContext });
+  if (loaded) {
+    log.info(`» using repo settings from ${loaded.file} on the default branch`);
+  }
+  const remote = isSelfHosted() ? null : await fetchRepoSettings(params);
+  return mergeRepoSettings(
+    DEFAULT_REPO_SETTINGS,
+    remote,
+    loaded ? repoConfigToSettings(loaded.config) : null
+  );
 }
 
 /**
  * Fetch repository settings from the Pullfrog API
- * Returns defaults if repo doesn't exist or fetch fails
+ * Returns null if repo doesn't exist or fetch fails
  */
 export async function fetchRepoSettings(params: {
   token: string;
   repoContext: RepoContext;
-}): Promise<RepoSettings> {
+}): Promise<RepoSettings | null> {
   const apiUrl = process.env.API_URL || "https://pullfrog.com";
   const timeoutMs = 30000;
   const controller = new AbortController();
@@ -46,38 +124,12 @@ export async function fetchRepoSettings(params: {
     clearTimeout(timeoutId);
 
     if (!response.ok) {
-      return {
-        defaultAgent: null,
-        modes: [],
-        web: "enabled",
-        search: "enabled",
-        write: "enabled",
-        bash: "restricted",
-      };
-    }
-
-    const settings = (await response.json()) as RepoSettings | null;
-    if (settings === null) {
-      return {
-        defaultAgent: null,
-        modes: [],
-        web: "enabled",
-        search: "enabled",
-        write: "enabled",
-        bash: "restricted",
-      };
+      return null;
     }
 
-    return settings;
+    return (await response.json()) as RepoSettings | null;
   } catch {
     clearTimeout(timeoutId);
-    return {
-      defaultAgent: null,
-      modes: [],
-      web: "enabled",
-      search: "enabled",
-      write: "enabled",
-      bash: "restricted",
-    };
+    return null;
   }
 }
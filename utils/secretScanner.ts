@@ -0,0 +1,333 @@
+/**
+ * Pattern-based secret detection
+ * Complements the exact-value matching in secrets.ts: catches well-known credential formats,
+ * private key blocks and high-entropy values assigned to secret-looking keys,
+ * including secrets the runner itself never held
+ */
+
+import { execFileSync } from "node:child_process";
+import { type } from "arktype";
+import { matchesGlobs } from "./glob.ts";
+
+interface SecretRule {
+  id: string;
+  description: string;
+  pattern: RegExp;
+}
+
+// well-known provider formats. patterns must not be anchored and must use the g flag
+const SECRET_RULES: SecretRule[] = [
+  {
+    id: "private-key",
+    description: "private key block",
+    pattern: /-----BEGIN (?:RSA |EC |DSA |OPENSSH |PGP |ENCRYPTED )?PRIVATE KEY(?: BLOCK)?-----/g,
+  },
+  {
+    id: "aws-access-key-id",
+    description: "AWS access key ID",
+    pattern: /\b(?:AKIA|ASIA)[0-9A-Z]{16}\b/g,
+  },
+  {
+    id: "github-token",
+    description: "GitHub token",
+    pattern: /\b(?:gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{60,})\b/g,
+  },
+  { id: "gitlab-token", description: "GitLab token", pattern: /\bglpat-[A-Za-z0-9_-]{20,}\b/g },
+  { id: "slack-token", description: "Slack token", pattern: /\bxox[abposr]-[A-Za-z0-9-]{10,}\b/g },
+  {
+    id: "slack-webhook",
+    description: "Slack webhook URL",
+    pattern: /https:\/\/hooks\.slack\.com\/services\/[A-Za-z0-9/]{20,}/g,
+  },
+  {
+    id: "stripe-key",
+    description: "Stripe secret key",
+    pattern: /\b[sr]k_live_[A-Za-z0-9]{20,}\b/g,
+  },
+  { id: "google-api-key", description: "Google API key", pattern: /\bAIza[0-9A-Za-z_-]{35}\b/g },
+  {
+    id: "anthropic-key",
+    description: "Anthropic API key",
+    pattern: /\bsk-ant-[A-Za-z0-9_-]{20,}/g,
+  },
+  {
+    id: "openai-key",
+    description: "OpenAI API key",
+    pattern: /\bsk-(?:proj-|svcacct-|admin-)?[A-Za-z0-9_-]{20,}T3BlbkFJ[A-Za-z0-9_-]{20,}/g,
+  },
+  { id: "npm-token", description: "npm access token", pattern: /\bnpm_[A-Za-z0-9]{36}\b/g },
+  {
+    id: "pypi-token",
+    description: "PyPI API token",
+    pattern: /\bpypi-AgEIcHlwaS5vcmc[A-Za-z0-9_-]{50,}/g,
+  },
+  {
+    id: "sendgrid-key",
+    description: "SendGrid API key",
+    pattern: /\bSG\.[A-Za-z0-9_-]{22}\.[A-Za-z0-9_-]{43}\b/g,
+  },
+];
+
+// values assigned to keys like `api_key = "..."` or `"clientSecret": "..."`
+const SECRET_KEYWORDS =
+  "secret|token|passw(?:or)?d|api[_-]?key|access[_-]?key|private[_-]?key|credential";
+const KEYWORD_ASSIGNMENT = new RegExp(
+  `(?:${SECRET_KEYWORDS})[A-Za-z0-9_-]*["']?\\s*(?:=|:|=>)\\s*["']?([A-Za-z0-9+/=_.~-]{16,})`,
+  "gi"
+);
+
+// in .env-style files every assignment is a candidate, whatever the key is called
+const ENV_ASSIGNMENT = /^\s*(?:export\s+)?[A-Za-z_][A-Za-z0-9_]*\s*=\s*["']?([^\s"'#]{16,})/g;
+const ENV_FILE_GLOBS = ["**/.env", "**/.env.*", "**/*.env"];
+
+// files that hold credentials by convention and are normally gitignored. templates are fine.
+export const SENSITIVE_FILE_GLOBS = [
+  "**/.env",
+  "**/.env.*",
+  "!**/.env.{example,sample,template,dist,defaults}",
+  "**/id_{rsa,dsa,ecdsa,ed25519}",
+  "**/*.{pem,key,p12,pfx,keystore,jks}",
+  "**/.netrc",
+  "**/.pgpass",
+];
+
+// staged files are read with a generous buffer so large files are scanned rather than skipped
+const MAX_SCAN_BUFFER = 256 * 1024 * 1024;
+
+// bits per character; random base64/hex secrets sit well above this, words and identifiers below
+const MIN_SECRET_ENTROPY = 3.5;
+
+export interface SecretFinding {
+  rule: string;
+  description: string;
+  /** masked match, safe to show in errors and logs */
+  preview: string;
+  /** file path, when known (diffs and file scans) */
+  path?: string;
+  /** 1-based line number within the scanned content or the new side of the diff */
+  line?: number;
+}
+
+export function shannonEntropy(value: string): number {
+  const counts = new Map<string, number>();
+  for (const char of value) counts.set(char, (counts.get(char) ?? 0) + 1);
+  let entropy = 0;
+  for (const count of counts.values()) {
+    const p = count / value.length;
+    entropy -= p * Math.log2(p);
+  }
+  return entropy;
+}
+
+function maskSecret(value: string): string {
+  if (value.length <= 8) return "****";
+  return `${value.slice(0, 4)}****${value.slice(-4)}`;
+}
+
+// filter out placeholders and code (`process.env.API_KEY`, `SECRET_NAME`) before the entropy check
+function isLikelySecretValue(value: string): boolean {
+  if (/example|changeme|placeholder|your[_-]/i.test(value)) return false;
+  if (/^[A-Za-z_$][\w$]*(?:\.[A-Za-z_$][\w$]*)+$/.test(value)) return false;
+  if (/^[A-Z0-9_]+$/.test(value) || /^[a-z]+(?:[_-][a-z]+)*$/.test(value)) return false;
+  // generated secrets mix letters and digits
+  return /\d/.test(value) && /[A-Za-z]/.test(value);
+}
+
+export const SecretsAllowlistSchema = type({
+  "+": "reject",
+  "values?": "string[]",
+  "paths?": "string[]",
+});
+
+export interface SecretsAllowlist {
+  /** exact matched values that are known false positives (test fixtures, public keys) */
+  values: string[];
+  /** path globs excluded from scanning */
+  paths: string[];
+}
+
+/**
+ * the `secretsAllowlist` repo setting with its lists filled in (empty when unset)
+ */
+export function resolveSecretsAllowlist(
+  settings: typeof SecretsAllowlistSchema.infer | undefined
+): SecretsAllowlist {
+  return { values: settings?.values ?? [], paths: settings?.paths ?? [] };
+}
+
+type LineFinding = Omit<SecretFinding, "path" | "line">;
+
+export function isEnvFile(path: string): boolean {
+  return matchesGlobs(path, ENV_FILE_GLOBS);
+}
+
+function scanLine(line: string, allowlist: SecretsAllowlist, envFile = false): LineFinding[] {
+  const findings: LineFinding[] = [];
+  const matched: string[] = [];
+  for (const rule of SECRET_RULES) {
+    for (const match of line.matchAll(rule.pattern)) {
+      if (allowlist.values.includes(match[0])) continue;
+      matched.push(match[0]);
+      findings.push({
+        rule: rule.id,
+        description: rule.description,
+        preview: maskSecret(match[0]),
+      });
+    }
+  }
+  const assignments = [...line.matchAll(KEYWORD_ASSIGNMENT)];
+  if (envFile) assignments.push(...line.matchAll(ENV_ASSIGNMENT));
+  const seen = new Set<string>();
+  for (const match of assignments) {
+    const value = match[1];
+    if (seen.has(value)) continue;
+    seen.add(value);
+    if (allowlist.values.includes(value) || !isLikelySecretValue(value)) continue;
+    if (shannonEntropy(value) < MIN_SECRET_ENTROPY) continue;
+    // already reported by a provider rule
+    if (matched.some((secret) => value.includes(secret))) continue;
+    findings.push({
+      rule: "high-entropy-assignment",
+      description: "high-entropy value assigned to a secret-like key",
+      preview: maskSecret(value),
+    });
+  }
+  return findings;
+}
+
+interface ScanOptions {
+  /** path of the scanned file (checked against the allowlist's path globs) */
+  path?: string | undefined;
+  allowlist: SecretsAllowlist;
+}
+
+/**
+ * scan text (a file, comment or PR body) for secrets
+ */
+export function scanForSecrets(content: string, options: ScanOptions): SecretFinding[] {
+  const allowlist = options.allowlist;
+  if (options.path && matchesGlobs(options.path, allowlist.paths)) return [];
+  const envFile = options.path ? isEnvFile(options.path) : false;
+  const findings: SecretFinding[] = [];
+  content.split("\n").forEach((line, i) => {
+    for (const finding of scanLine(line, allowlist, envFile)) {
+      findings.push({ ...finding, ...(options.path ? { path: options.path } : {}), line: i + 1 });
+    }
+  });
+  return findings;
+}
+
+/**
+ * scan only the lines a unified diff adds, so removing a leaked secret is never blocked
+ */
+export function scanDiffForSecrets(
+  diff: string,
+  options: Pick<ScanOptions, "allowlist">
+): SecretFinding[] {
+  const allowlist = options.allowlist;
+  const findings: SecretFinding[] = [];
+  let path: string | undefined;
+  let line = 0;
+  for (const raw of diff.split("\n")) {
+    if (raw.startsWith("+++ ")) {
+      path = raw.slice(4).replace(/^b\//, "");
+      if (path === "/dev/null") path = undefined;
+      continue;
+    }
+    const hunk = raw.match(/^@@ -\d+(?:,\d+)? \+(\d+)/);
+    if (hunk) {
+      line = Number(hunk[1]);
+      continue;
+    }
+    if (raw.startsWith("+")) {
+      if (!path || !matchesGlobs(path, allowlist.paths)) {
+        for (const finding of scanLine(raw.slice(1), allowlist, isEnvFile(path ?? ""))) {
+          findings.push({ ...finding, ...(path ? { path } : {}), line });
+        }
+      }
+      line++;
+    } else if (!raw.startsWith("-")) {
+      line++;
+    }
+  }
+  return findings;
+}
+
+export function formatSecretFindings(findings: SecretFinding[]): string {
+  return findings
+    .map((f) => {
+      const location = f.path ? (f.line ? `${f.path}:${f.line}` : f.path) : `line ${f.line}`;
+      return `  - ${location}: ${f.description} (${f.preview})`;
+    })
+    .join("\n");
+}
+
+export function findSensitiveFiles(paths: string[]): string[] {
+  return paths.filter((path) => matchesGlobs(path, SENSITIVE_FILE_GLOBS));
+}
+
+function git(args: string[], cwd: string): Buffer {
+  return execFileSync("git", args, {
+    cwd,
+    maxBuffer: MAX_SCAN_BUFFER,
+    stdio: ["ignore", "pipe", "pipe"],
+  });
+}
+
+// printable ASCII runs, like strings(1), so keys embedded in binaries are still caught
+function extractStrings(content: Buffer): string {
+  return content.toString("latin1").match(/[\x20-\x7e]{8,}/g)?.join("\n") ?? "";
+}
+
+export interface StagedScan {
+  findings: SecretFinding[];
+  /** staged diff plus strings from binary files, for exact-value checks */
+  text: string;
+  /** newly added files that hold credentials by convention (.env, id_rsa, ...) */
+  sensitiveFiles: string[];
+}
+
+/**
+ * scan what is actually staged (`git diff --cached`): added lines of text files,
+ * printable strings of binary files, and new files that should never be committed
+ */
+export function scanStagedChanges(
+  options: { cwd?: string; allowlist: SecretsAllowlist }
+): StagedScan {
+  const cwd = options.cwd ?? process.cwd();
+  const allowlist = options.allowlist;
+  const list = (args: string[]) =>
+    git(["diff", "--cached", "--no-renames", "-z", ...args], cwd)
+      .toString("utf-8")
+      .split("\0")
+      .filter(Boolean);
+
+  const diff = git(["diff", "--cached", "--no-renames", "--no-color", "--no-ext-diff"], cwd);
+  const findings = scanDiffForSecrets(diff.toString("utf-8"), { allowlist });
+  const texts = [diff.toString("utf-8")];
+
+  // numstat reports binary files as "-\t-\t<path>"; deleted files are left out
+  const binaryPaths = list(["--numstat", "--diff-filter=d"])
+    .filter((entry) => entry.startsWith("-\t-\t"))
+    .map((entry) => entry.slice(4));
+  for (const path of binaryPaths) {
+    if (matchesGlobs(path, allowlist.paths)) continue;
+    const strings = extractStrings(git(["cat-file", "blob", `:${path}`], cwd));
+    texts.push(strings);
+    for (const finding of scanForSecrets(strings, { path, allowlist })) {
+      // line numbers within extracted strings mean nothing to the reader
+      findings.push({
+        rule: finding.rule,
+        description: finding.description,
+        preview: finding.preview,
+        path,
+      });
+    }
+  }
+
+  return {
+    findings,
+    text: texts.join("\n"),
+    sensitiveFiles: findSensitiveFiles(list(["--name-only", "--diff-filter=A"])),
+  };
+}
@@ -0,0 +1,22 @@
+import * as core from "@actions/core";
+
+/**
+ * Self-hosted mode: the action never contacts the Pullfrog API.
+ * Repo settings and modes come from the checked-in config file, installation tokens are minted with
+ * the repo's own GitHub App (GITHUB_APP_ID/GITHUB_PRIVATE_KEY), and links to Pullfrog-hosted pages
+ * are left out of comments.
+ *
+ * Enabled with the `self_hosted` input (on both actions) or the PULLFROG_SELF_HOSTED env var.
+ */
+export function isSelfHosted(): boolean {
+  const value = core.getInput("self_hosted") || process.env.PULLFROG_SELF_HOSTED || "";
+  return value.toLowerCase() === "true";
+}
+
+/**
+ * base URL of the Pullfrog API, or null in self-hosted mode
+ */
+export function getPullfrogApiUrl(): string | null {
+  if (isSelfHosted()) return null;
+  return process.env.API_URL || "https://pullfrog.com";
+}
@@ -8,6 +8,8 @@ export interface SpawnOptions {
   timeout?: number;
   cwd?: string;
   stdio?: ("pipe" | "ignore" | "inherit")[];
+  /** stops the process (SIGTERM, then SIGKILL) when aborted */
+  signal?: AbortSignal;
   onStdout?: (chunk: string) => void;
   onStderr?: (chunk: string) => void;
 }
@@ -23,7 +25,7 @@ export interface SpawnResult {
  * Spawn a subprocess with streaming callbacks and buffered results
  */
 export async function spawn(options: SpawnOptions): Promise<SpawnResult> {
-  const { cmd, args, env, input, timeout, cwd, stdio, onStdout, onStderr } = options;
+  const { cmd, args, env, input, timeout, cwd, stdio, signal, onStdout, onStderr } = options;
 
   const startTime = Date.now();
   let stdoutBuffer = "";
@@ -56,6 +58,22 @@ export async function spawn(options: SpawnOptions): Promise<SpawnResult> {
       }, timeout);
     }
 
+    let isAborted = false;
+    const onAbort = () => {
+      isAborted = true;
+      child.kill("SIGTERM");
+      setTimeout(() => {
+        if (child.exitCode === null) {
+          child.kill("SIGKILL");
+        }
+      }, 5000).unref();
+    };
+    if (signal?.aborted) {
+      onAbort();
+    } else {
+      signal?.addEventListener("abort", onAbort, { once: true });
+    }
+
     if (child.stdout) {
       child.stdout.on("data", (data: Buffer) => {
         const chunk = data.toString();
@@ -79,6 +97,8 @@ export async function spawn(options: SpawnOptions): Promise<SpawnResult> {
         clearTimeout(timeoutId);
       }
 
+      signal?.removeEventListener("abort", onAbort);
+
       if (isTimedOut) {
         reject(new Error(`Process timed out after ${timeout}ms`));
         return;
@@ -87,7 +107,8 @@ export async function spawn(options: SpawnOptions): Promise<SpawnResult> {
       resolve({
         stdout: stdoutBuffer,
         stderr: stderrBuffer,
-        exitCode: exitCode || 0,
+        // a process killed on abort exits with a null code
+        exitCode: exitCode || (isAborted ? 1 : 0),
         durationMs,
       });
     });
@@ -98,6 +119,7 @@ export async function spawn(options: SpawnOptions): Promise<SpawnResult> {
       if (timeoutId) {
         clearTimeout(timeoutId);
       }
+      signal?.removeEventListener("abort", onAbort);
 
       // log spawn errors for debugging
       console.error(`[spawn] Process spawn error: ${error.message}`);
@@ -2,6 +2,7 @@ import assert from "node:assert/strict";
 import * as core from "@actions/core";
 import { log } from "./cli.ts";
 import { acquireNewToken } from "./github.ts";
+import { getGitHubApiUrl } from "./githubHost.ts";
 
 // re-export for get-installation-token action
 export { acquireNewToken as acquireInstallationToken };
@@ -39,10 +40,8 @@ export function getGitHubInstallationToken(): string {
 }
 
 export async function revokeGitHubInstallationToken(token: string): Promise<void> {
-  const apiUrl = process.env.GITHUB_API_URL || "https://api.github.com";
-
   try {
-    await fetch(`${apiUrl}/installation/token`, {
+    await fetch(`${getGitHubApiUrl()}/installation/token`, {
       method: "DELETE",
       headers: {
         Accept: "application/vnd.github+json",
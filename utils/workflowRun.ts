@@ -1,3 +1,5 @@
+import { getPullfrogApiUrl } from "./selfHosted.ts";
+
 export interface WorkflowRunInfo {
   progressCommentId: string | null;
 }
@@ -7,7 +9,9 @@ export interface WorkflowRunInfo {
  * Returns the pre-created progress comment ID if one exists.
  */
 export async function fetchWorkflowRunInfo(runId: string): Promise<WorkflowRunInfo> {
-  const apiUrl = process.env.API_URL || "https://pullfrog.com";
+  const apiUrl = getPullfrogApiUrl();
+  // progress comments are only pre-created by the Pullfrog API
+  if (!apiUrl) return { progressCommentId: null };
   const timeoutMs = 30000;
   const controller = new AbortController();
   const timeoutId = setTimeout(() => controller.abort(), timeoutMs);
@@ -0,0 +1,112 @@
+import { execFileSync } from 'node:child_process';
+import { existsSync, mkdirSync, mkdtempSync, readFileSync, rmSync, writeFileSync } from 'node:fs';
+import { tmpdir } from 'node:os';
+import { join } from 'node:path';
+import {
+  assertWritable,
+  commitsTouching,
+  diffWorkingTree,
+  findWritePolicyViolations,
+  resolveWritePolicy,
+  revertPaths,
+  snapshotWorkingTree,
+} from './writePolicy.ts';
+
+describe('resolveWritePolicy', () => {
+  it('denies everything when write is disabled', () => {
+    const policy = resolveWritePolicy({ write: 'disabled' }, { writePolicy: { deny: ['docs/**'] } });
+    expect(findWritePolicyViolations(['src/index.ts'], policy)).toHaveLength(1);
+  });
+
+  it('uses the repo deny list otherwise', () => {
+    const policy = resolveWritePolicy({ write: 'enabled' }, { writePolicy: { deny: ['.github/workflows/**', 'migrations/**'] } });
+    expect(findWritePolicyViolations(['.github/workflows/ci.yml', 'src/migrations.ts', 'migrations/001.sql'], policy)).toEqual([
+      { path: '.github/workflows/ci.yml', rule: '.github/workflows/**' },
+      { path: 'migrations/001.sql', rule: 'migrations/**' },
+    ]);
+  });
+
+  it('explains rejected paths', () => {
+    expect(() => assertWritable(['migrations/001.sql'], { deny: ['migrations/**'] }, 'Commit')).toThrow(
+      /Commit blocked by the repository write policy[\s\S]*migrations\/001\.sql \(matches "migrations\/\*\*"\)/
+    );
+  });
+});
+
+describe('working tree snapshots', () => {
+  let repo: string;
+  let originalCwd: string;
+  const git = (...args: string[]) => execFileSync('git', args, { cwd: repo, stdio: 'pipe' });
+
+  beforeEach(() => {
+    repo = mkdtempSync(join(tmpdir(), 'pullfrog-write-policy-test-'));
+    git('init', '-q');
+    git('config', 'user.email', 'test@example.com');
+    git('config', 'user.name', 'test');
+    mkdirSync(join(repo, 'migrations'));
+    writeFileSync(join(repo, 'migrations', '001.sql'), 'create table a;');
+    writeFileSync(join(repo, 'README.md'), 'readme');
+    git('add', '.');
+    git('commit', '-q', '-m', 'init');
+    // git commands run through spawnSync, which uses the real working directory
+    originalCwd = process.cwd();
+    process.chdir(repo);
+  });
+
+  afterEach(() => {
+    process.chdir(originalCwd);
+    rmSync(repo, { recursive: true, force: true });
+  });
+
+  it('detects edits, new files and commits since the snapshot', () => {
+    writeFileSync(join(repo, 'README.md'), 'already dirty');
+    const before = snapshotWorkingTree();
+
+    writeFileSync(join(repo, 'migrations', '001.sql'), 'drop table a;');
+    writeFileSync(join(repo, 'migrations', '002.sql'), 'create table b;');
+    expect(diffWorkingTree(before).paths).toEqual(['migrations/001.sql', 'migrations/002.sql']);
+
+    git('add', 'migrations');
+    git('commit', '-q', '-m', 'change');
+    expect(diffWorkingTree(before)).toMatchObject({
+      paths: ['migrations/001.sql', 'migrations/002.sql'],
+      committed: true,
+    });
+  });
+
+  it('reverts uncommitted denied paths and reports commits without moving HEAD', () => {
+    const before = snapshotWorkingTree();
+    writeFileSync(join(repo, 'migrations', '001.sql'), 'drop table a;');
+    git('add', 'migrations/001.sql');
+    git('commit', '-q', '-m', 'drop a');
+    const head = git('rev-parse', 'HEAD').toString().trim();
+    writeFileSync(join(repo, 'migrations', '002.sql'), 'create table b;');
+    writeFileSync(join(repo, 'README.md'), 'allowed edit');
+    git('add', 'migrations/002.sql');
+
+    const { committed } = diffWorkingTree(before);
+    expect(committed).toBe(true);
+    revertPaths(['migrations/001.sql', 'migrations/002.sql'], before);
+
+    expect(git('rev-parse', 'HEAD').toString().trim()).toBe(head);
+    expect(readFileSync(join(repo, 'migrations', '001.sql'), 'utf-8')).toBe('drop table a;');
+    expect(existsSync(join(repo, 'migrations', '002.sql'))).toBe(false);
+    expect(git('diff', '--cached', '--name-only').toString()).toBe('');
+    expect(readFileSync(join(repo, 'README.md'), 'utf-8')).toBe('allowed edit');
+    expect(commitsTouching(before.head, ['migrations/001.sql', 'migrations/002.sql'])).toEqual([`${head.slice(0, 7)} drop a`]);
+  });
+
+  it('restores the contents a denied path had at the snapshot, not HEAD', () => {
+    writeFileSync(join(repo, 'migrations', '001.sql'), 'create table a (id int);');
+    writeFileSync(join(repo, 'migrations', '003.sql'), 'create table c;');
+    const before = snapshotWorkingTree();
+
+    writeFileSync(join(repo, 'migrations', '001.sql'), 'drop table a;');
+    rmSync(join(repo, 'migrations', '003.sql'));
+    revertPaths(diffWorkingTree(before).paths, before);
+
+    expect(readFileSync(join(repo, 'migrations', '001.sql'), 'utf-8')).toBe('create table a (id int);');
+    expect(readFileSync(join(repo, 'migrations', '003.sql'), 'utf-8')).toBe('create table c;');
+    expect(diffWorkingTree(before).paths).toEqual([]);
+  });
+});
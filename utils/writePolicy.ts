@@ -0,0 +1,192 @@
+import { execFileSync } from "node:child_process";
+import { existsSync, mkdirSync, writeFileSync } from "node:fs";
+import { dirname, join } from "node:path";
+import { $ } from "./shell.ts";
+import { globToRegExp } from "./glob.ts";
+import type { ResolvedPayload } from "./payload.ts";
+import type { RepoSettings } from "./repoSettings.ts";
+
+/**
+ * repo-relative paths the agent must not modify, enforced by the MCP server
+ * (commit_files, create_pull_request, bash) regardless of the agent's own tool flags
+ */
+export interface WritePolicy {
+  deny: string[];
+}
+
+export class WritePolicyError extends Error {}
+
+export interface WritePolicyViolation {
+  path: string;
+  rule: string;
+}
+
+/**
+ * combine the repo's write policy with the `write` permission.
+ * `write: disabled` denies every path.
+ */
+export function resolveWritePolicy(
+  payload: Pick<ResolvedPayload, "write">,
+  repoSettings: Pick<RepoSettings, "writePolicy">
+): WritePolicy {
+  if (payload.write === "disabled") {
+    return { deny: ["**"] };
+  }
+  return { deny: repoSettings.writePolicy?.deny ?? [] };
+}
+
+export function findWritePolicyViolations(
+  paths: string[],
+  policy: WritePolicy
+): WritePolicyViolation[] {
+  const violations: WritePolicyViolation[] = [];
+  for (const path of paths) {
+    const rule = policy.deny.find((glob) => globToRegExp(glob).test(path));
+    if (rule) violations.push({ path, rule });
+  }
+  return violations;
+}
+
+export function formatWritePolicyViolations(violations: WritePolicyViolation[]): string {
+  return violations.map((v) => `  - ${v.path} (matches "${v.rule}")`).join("\n");
+}
+
+/**
+ * throw a WritePolicyError describing every denied path
+ */
+export function assertWritable(paths: string[], policy: WritePolicy, action: string): void {
+  const violations = findWritePolicyViolations(paths, policy);
+  if (violations.length === 0) return;
+  throw new WritePolicyError(
+    `${action} blocked by the repository write policy. These paths may not be modified:\n` +
+      `${formatWritePolicyViolations(violations)}\n` +
+      "Leave these files unchanged and mention in your summary that they need a manual change."
+  );
+}
+
+/**
+ * paths changed between two revisions.
+ * renames are listed as delete + add so both sides are checked.
+ */
+export function getChangedPaths(from: string, to: string): string[] {
+  const args = ["diff", "--name-only", "--no-renames", `${from}..${to}`];
+  const output = $("git", args, { log: false });
+  return output ? output.split("\n") : [];
+}
+
+/**
+ * state of the working tree: HEAD plus a content hash for every dirty or untracked file.
+ * the contents are written to the object database, so revertPaths can restore them
+ */
+export interface WorkingTreeSnapshot {
+  head: string;
+  branch: string;
+  /** blob hash per dirty path, or "deleted" */
+  files: Map<string, string>;
+}
+
+// tracked files that differ from HEAD (staged or not) plus untracked, non-ignored files
+function listDirtyPaths(root: string): string[] {
+  const tracked = $("git", ["diff", "HEAD", "--name-only", "--no-renames", "-z"], {
+    log: false,
+    cwd: root,
+  });
+  const untracked = $("git", ["ls-files", "--others", "--exclude-standard", "-z"], {
+    log: false,
+    cwd: root,
+  });
+  return [...new Set(`${tracked}\0${untracked}`.split("\0").filter(Boolean))];
+}
+
+export function snapshotWorkingTree(): WorkingTreeSnapshot {
+  const head = $("git", ["rev-parse", "HEAD"], { log: false });
+  const branch = $("git", ["rev-parse", "--abbrev-ref", "HEAD"], { log: false });
+  const root = $("git", ["rev-parse", "--show-toplevel"], { log: false });
+  const files = new Map<string, string>();
+  const paths = listDirtyPaths(root);
+  const existing = paths.filter((path) => existsSync(`${root}/${path}`));
+  // paths go through stdin: a large untracked tree would overflow the argument list
+  const hashes = existing.length
+    ? execFileSync("git", ["hash-object", "-w", "--stdin-paths"], {
+        cwd: root,
+        input: `${existing.join("\n")}\n`,
+        encoding: "utf-8",
+        maxBuffer: 64 * 1024 * 1024,
+      }).split("\n")
+    : [];
+  existing.forEach((path, i) => files.set(path, hashes[i] ?? ""));
+  for (const path of paths) {
+    if (!files.has(path)) files.set(path, "deleted");
+  }
+  return { head, branch, files };
+}
+
+/**
+ * paths whose content differs between a snapshot and the current working tree.
+ * commits made on the same branch count too; switching branches does not.
+ */
+export function diffWorkingTree(before: WorkingTreeSnapshot): {
+  after: WorkingTreeSnapshot;
+  paths: string[];
+  committed: boolean;
+} {
+  const after = snapshotWorkingTree();
+  const changed = new Set<string>();
+  const committed = after.head !== before.head && after.branch === before.branch;
+  if (committed) {
+    for (const path of getChangedPaths(before.head, after.head)) changed.add(path);
+  }
+  for (const path of new Set([...before.files.keys(), ...after.files.keys()])) {
+    if (before.files.get(path) !== after.files.get(path)) changed.add(path);
+  }
+  return { after, paths: [...changed].sort(), committed };
+}
+
+/**
+ * undo uncommitted changes to the given paths since the snapshot: files that were already dirty
+ * get the snapshot's contents back (so earlier edits survive), other files are restored from HEAD
+ * or removed. the index is reset to HEAD for these paths. HEAD never moves, so commits made since
+ * the snapshot (by commit_files or anyone else) are left alone - see commitsTouching.
+ */
+export function revertPaths(paths: string[], before: WorkingTreeSnapshot): void {
+  const root = $("git", ["rev-parse", "--show-toplevel"], { log: false });
+  for (const path of paths) {
+    const saved = before.files.get(path);
+    const tracked = $("git", ["ls-tree", "--name-only", "HEAD", "--", path], {
+      log: false,
+      cwd: root,
+    });
+    if (saved === undefined && tracked) {
+      $("git", ["checkout", "HEAD", "--", path], { log: false, cwd: root });
+      continue;
+    }
+
+    if (tracked) {
+      $("git", ["reset", "-q", "HEAD", "--", path], { log: false, cwd: root });
+    } else {
+      $("git", ["rm", "-q", "-f", "--cached", "--ignore-unmatch", "--", path], {
+        log: false,
+        cwd: root,
+      });
+    }
+    if (saved === undefined || saved === "deleted") {
+      $("rm", ["-f", "--", path], { log: false, cwd: root });
+    } else {
+      // read as a buffer: $ trims its output, which would corrupt binary files
+      const content = execFileSync("git", ["cat-file", "blob", saved], { cwd: root });
+      mkdirSync(dirname(join(root, path)), { recursive: true });
+      writeFileSync(join(root, path), content);
+    }
+  }
+}
+
+/**
+ * commits between `from` and HEAD that change any of the given paths, as "<short sha> <subject>"
+ */
+export function commitsTouching(from: string, paths: string[]): string[] {
+  if (paths.length === 0) return [];
+  const output = $("git", ["log", "--format=%h %s", `${from}..HEAD`, "--", ...paths], {
+    log: false,
+  });
+  return output ? output.split("\n") : [];
+}
@@ -0,0 +1,26 @@
+#!/usr/bin/env node
+
+/**
+ * validate the checked-in repo config (.pullfrog.yml or .pullfrog/config.yml)
+ * usage: node validate-config.ts [repo-dir]
+ * prints every schema error and exits non-zero if the file is invalid
+ */
+
+import { resolve } from "node:path";
+import { log } from "./utils/cli.ts";
+import { loadRepoConfig, REPO_CONFIG_FILES, RepoConfigError } from "./utils/repoConfig.ts";
+
+const cwd = resolve(process.argv[2] ?? process.cwd());
+
+try {
+  const loaded = loadRepoConfig(cwd);
+  if (loaded) {
+    log.info(`» ${loaded.file} is valid`);
+  } else {
+    log.info(`» no repo config found (looked for ${REPO_CONFIG_FILES.join(", ")})`);
+  }
+} catch (error) {
+  if (!(error instanceof RepoConfigError)) throw error;
+  log.error(error.message);
+  process.exitCode = 1;
+}